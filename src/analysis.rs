@@ -0,0 +1,451 @@
+use crate::transformation::{self, MotionType};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B",
+];
+
+// Shortest run of identical root motions reported as a cycle
+const MIN_CYCLE_STEPS: usize = 3;
+
+// Chord qualities recognised by the template matcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quality {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    Dominant,
+    MajorSeventh,
+    MinorSeventh,
+    HalfDiminished,
+    DiminishedSeventh,
+}
+
+impl Quality {
+    fn suffix(self) -> &'static str {
+        match self {
+            Quality::Major => "",
+            Quality::Minor => "m",
+            Quality::Diminished => "°",
+            Quality::Augmented => "+",
+            Quality::Dominant => "7",
+            Quality::MajorSeventh => "maj7",
+            Quality::MinorSeventh => "m7",
+            Quality::HalfDiminished => "ø7",
+            Quality::DiminishedSeventh => "°7",
+        }
+    }
+
+    // Rough brightness ordering, augmented highest and diminished lowest
+    pub fn brightness(self) -> i32 {
+        match self {
+            Quality::Augmented => 3,
+            Quality::MajorSeventh => 2,
+            Quality::Major => 1,
+            Quality::Dominant => 0,
+            Quality::MinorSeventh => -1,
+            Quality::Minor => -2,
+            Quality::HalfDiminished => -3,
+            Quality::Diminished => -4,
+            Quality::DiminishedSeventh => -5,
+        }
+    }
+
+    fn family(self) -> Quality {
+        match self {
+            Quality::Major | Quality::MajorSeventh => Quality::Major,
+            Quality::Minor | Quality::MinorSeventh => Quality::Minor,
+            Quality::Diminished | Quality::HalfDiminished | Quality::DiminishedSeventh => {
+                Quality::Diminished
+            }
+            Quality::Augmented => Quality::Augmented,
+            Quality::Dominant => Quality::Dominant,
+        }
+    }
+
+    // Hue of the quality's family, the families spread evenly over the
+    // palette in FAMILIES order
+    pub fn hue(self) -> f32 {
+        let family = self.family();
+        let index = FAMILIES
+            .iter()
+            .position(|&(_, quality)| quality == family)
+            .unwrap_or(0);
+        (index as f32 + 0.5) / FAMILIES.len() as f32
+    }
+}
+
+// Quality families that --color-by quality tells apart, darkest first;
+// seventh chords belong to their triad's family, dominant sevenths aside
+pub const FAMILIES: [(&str, Quality); 5] = [
+    ("diminished", Quality::Diminished),
+    ("minor", Quality::Minor),
+    ("dominant", Quality::Dominant),
+    ("major", Quality::Major),
+    ("augmented", Quality::Augmented),
+];
+
+// Interval templates relative to the root, most specific first
+const TEMPLATES: [(&[i32], Quality); 12] = [
+    (&[0, 4, 7, 10], Quality::Dominant),
+    (&[0, 4, 7, 11], Quality::MajorSeventh),
+    (&[0, 3, 7, 10], Quality::MinorSeventh),
+    (&[0, 3, 6, 10], Quality::HalfDiminished),
+    (&[0, 3, 6, 9], Quality::DiminishedSeventh),
+    (&[0, 4, 7], Quality::Major),
+    (&[0, 3, 7], Quality::Minor),
+    (&[0, 3, 6], Quality::Diminished),
+    (&[0, 4, 8], Quality::Augmented),
+    (&[0, 4, 10], Quality::Dominant), // Seventh chord without its fifth
+    (&[0, 4], Quality::Major),        // Bare third
+    (&[0, 3], Quality::Minor),
+];
+
+// A detected chord: root pitch class and quality
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChordName {
+    pub root: i32,
+    pub quality: Quality,
+}
+
+impl fmt::Display for ChordName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            NOTE_NAMES[self.root as usize],
+            self.quality.suffix()
+        )
+    }
+}
+
+// Name a four-voice chord, if its pitch classes match a known template
+pub fn detect_chord(chord: [i32; 4]) -> Option<ChordName> {
+    let mut pcs: Vec<i32> = chord.iter().map(|p| p.rem_euclid(12)).collect();
+    pcs.sort_unstable();
+    pcs.dedup();
+
+    for (template, quality) in TEMPLATES {
+        // Every pitch class must belong to the template rooted somewhere
+        for &root in &pcs {
+            let fits = pcs
+                .iter()
+                .all(|pc| template.contains(&(pc - root).rem_euclid(12)));
+            if fits && template.len() == pcs.len() {
+                return Some(ChordName { root, quality });
+            }
+        }
+    }
+
+    None
+}
+
+// A chord change: the frame where it starts and the chord it names
+#[derive(Debug, Clone, Copy)]
+pub struct Change {
+    pub frame: usize,
+    pub chord: ChordName,
+}
+
+// Collapse the frame grid into a list of named chord changes
+pub fn chord_changes(frames: &[[i32; 4]]) -> Vec<Change> {
+    let mut changes: Vec<Change> = Vec::new();
+    for (frame, &notes) in frames.iter().enumerate() {
+        if let Some(chord) = detect_chord(notes)
+            && changes.last().is_none_or(|last| last.chord != chord)
+        {
+            changes.push(Change { frame, chord });
+        }
+    }
+    changes
+}
+
+// A labelled range of keyframes (first..=last frame index)
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub first: usize,
+    pub last: usize,
+    pub label: String,
+}
+
+// Name of a cycle of constant root motion, None for motions that are no
+// cycle: repeating the chord, or a tritone whose second step is back home
+fn cycle_name(interval: i32, changes: &[Change]) -> Option<String> {
+    let same_quality = changes
+        .windows(2)
+        .all(|w| w[0].chord.quality == w[1].chord.quality);
+    let name = match interval {
+        5 => "descending-fifths sequence",
+        7 => "ascending-fifths sequence",
+        3 | 4 | 8 | 9 if same_quality => "chromatic mediant chain",
+        3 | 9 => "minor-third cycle",
+        4 | 8 => "major-third cycle",
+        2 | 10 => "whole-tone cycle",
+        1 | 11 => "semitone cycle",
+        _ => return None,
+    };
+    Some(name.into())
+}
+
+fn root_motion(a: &Change, b: &Change) -> i32 {
+    (b.chord.root - a.chord.root).rem_euclid(12)
+}
+
+// Find runs of constant root motion (interval cycles) and repeating
+// two-step root patterns (harmonic sequences) among the chord changes
+pub fn detect_sequences(changes: &[Change]) -> Vec<Span> {
+    let motions: Vec<i32> = changes
+        .windows(2)
+        .map(|w| root_motion(&w[0], &w[1]))
+        .collect();
+    let mut spans = Vec::new();
+
+    // Interval cycles
+    let mut start = 0;
+    while start < motions.len() {
+        let mut end = start;
+        while end + 1 < motions.len() && motions[end + 1] == motions[start] {
+            end += 1;
+        }
+        let steps = end - start + 1;
+        let run = &changes[start..=end + 1];
+        if steps >= MIN_CYCLE_STEPS
+            && let Some(label) = cycle_name(motions[start], run)
+        {
+            spans.push(Span {
+                first: run[0].frame,
+                last: run[run.len() - 1].frame,
+                label,
+            });
+        }
+        start = end + 1;
+    }
+
+    // Two-step sequences: a, b, a, b with a != b, transposing each period
+    // (a + b = 0 would just be oscillating between two chords)
+    let mut start = 0;
+    while start + 3 < motions.len() {
+        let (a, b) = (motions[start], motions[start + 1]);
+        let mut end = start + 1;
+        while end + 1 < motions.len() && motions[end + 1] == motions[end - 1] {
+            end += 1;
+        }
+        if a != b && (a + b) % 12 != 0 && end - start + 1 >= 4 {
+            let run = &changes[start..=end + 1];
+            spans.push(Span {
+                first: run[0].frame,
+                last: run[run.len() - 1].frame,
+                label: format!("sequence (+{a}/+{b})"),
+            });
+            start = end + 1;
+        } else {
+            start += 1;
+        }
+    }
+
+    spans.sort_by_key(|span| span.first);
+    spans
+}
+
+// Phrase endings told apart by what the cadence arrives on and how
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CadenceKind {
+    Perfect,   // Dominant to tonic, both in root position, tonic in the top voice
+    Imperfect, // Dominant to tonic otherwise voiced
+    Half,      // Arrival on a lingering dominant
+}
+
+impl CadenceKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            CadenceKind::Perfect => "PAC",
+            CadenceKind::Imperfect => "IAC",
+            CadenceKind::Half => "HC",
+        }
+    }
+}
+
+// A cadence: the frame where its arrival chord starts, and the two chords
+#[derive(Debug, Clone, Copy)]
+pub struct Cadence {
+    pub frame: usize,
+    pub kind: CadenceKind,
+    pub from: ChordName,
+    pub to: ChordName,
+}
+
+// Root of the chord sits in the lowest voice
+fn root_position(chord: [i32; 4], name: ChordName) -> bool {
+    chord
+        .iter()
+        .min()
+        .is_some_and(|p| p.rem_euclid(12) == name.root)
+}
+
+// Find cadences among the chord changes without knowing the key: a major
+// or dominant chord whose root falls a fifth onto a major or minor triad is
+// authentic, and a major triad reached from I, IV or ii and held longer
+// than that chord is a half cadence. Phrase endings linger, so an authentic
+// arrival must last at least as long as its dominant; `durations` holds the
+// length of each step between frames. Changes inside a sequence are the
+// sequence's own motion and are skipped.
+pub fn detect_cadences(
+    frames: &[[i32; 4]],
+    durations: &[f32],
+    changes: &[Change],
+    sequences: &[Span],
+) -> Vec<Cadence> {
+    // Length of each change up to the next, the last one held to the end
+    let lengths: Vec<f32> = changes
+        .iter()
+        .enumerate()
+        .map(|(i, change)| match changes.get(i + 1) {
+            Some(next) => durations[change.frame..next.frame.min(durations.len())]
+                .iter()
+                .sum(),
+            None => f32::INFINITY,
+        })
+        .collect();
+
+    let mut cadences = Vec::new();
+    for (i, pair) in changes.windows(2).enumerate() {
+        let (from, to) = (pair[0], pair[1]);
+        let inside = sequences
+            .iter()
+            .any(|span| span.first < to.frame && to.frame < span.last);
+        if inside {
+            continue;
+        }
+        let (approach, arrival) = (lengths[i], lengths[i + 1]);
+        let dominant = matches!(from.chord.quality, Quality::Major | Quality::Dominant);
+        let tonic = matches!(to.chord.quality, Quality::Major | Quality::Minor);
+        let kind = match root_motion(&from, &to) {
+            5 if dominant && tonic && arrival >= approach => {
+                let arrival_chord = frames[to.frame];
+                let top = arrival_chord.iter().max().map(|p| p.rem_euclid(12));
+                let perfect = root_position(frames[from.frame], from.chord)
+                    && root_position(arrival_chord, to.chord)
+                    && top == Some(to.chord.root);
+                if perfect {
+                    CadenceKind::Perfect
+                } else {
+                    CadenceKind::Imperfect
+                }
+            }
+            7 | 2 | 5
+                if to.chord.quality == Quality::Major
+                    && from.chord.quality.family() != Quality::Dominant
+                    && arrival > approach =>
+            {
+                CadenceKind::Half
+            }
+            _ => continue,
+        };
+        cadences.push(Cadence {
+            frame: to.frame,
+            kind,
+            from: from.chord,
+            to: to.chord,
+        });
+    }
+    cadences
+}
+
+// Write a plain-text analysis report
+pub fn export(
+    path: &Path,
+    changes: &[Change],
+    sequences: &[Span],
+    cadences: &[Cadence],
+    motion_types: &[[MotionType; 6]],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut report = String::new();
+
+    report.push_str("# Chord changes (frame: chord)\n");
+    for change in changes {
+        report.push_str(&format!("{:05}: {}\n", change.frame, change.chord));
+    }
+
+    report.push_str("\n# Sequences and interval cycles (first-last frame: description)\n");
+    for span in sequences {
+        report.push_str(&format!(
+            "{:05}-{:05}: {}\n",
+            span.first, span.last, span.label
+        ));
+    }
+
+    report.push_str("\n# Cadences (frame: kind, approach -> arrival)\n");
+    for cadence in cadences {
+        report.push_str(&format!(
+            "{:05}: {} {} -> {}\n",
+            cadence.frame,
+            cadence.kind.label(),
+            cadence.from,
+            cadence.to
+        ));
+    }
+
+    report.push_str(
+        "\n# Motion types per step (step: pairs 12 13 14 23 24 34; \
+         P parallel, S similar, O oblique, C contrary, - static)\n",
+    );
+    for (step, types) in motion_types.iter().enumerate() {
+        if types.iter().any(|&t| t != MotionType::Static) {
+            report.push_str(&format!(
+                "{:05}: {}\n",
+                step,
+                transformation::motion_label(types)
+            ));
+        }
+    }
+
+    fs::write(path, report)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Close-position triad over a doubled root, top voice first
+    fn triad(root: i32, third: i32) -> [i32; 4] {
+        [root + 19, root + 12 + third, root + 12, root]
+    }
+
+    fn labels(frames: &[[i32; 4]]) -> Vec<String> {
+        detect_sequences(&chord_changes(frames))
+            .into_iter()
+            .map(|span| span.label)
+            .collect()
+    }
+
+    #[test]
+    fn descending_fifths_are_one_span() {
+        let frames = [48, 53, 46, 51, 44].map(|root| triad(root, 4));
+        let spans = detect_sequences(&chord_changes(&frames));
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].label, "descending-fifths sequence");
+        assert_eq!((spans[0].first, spans[0].last), (0, 4));
+    }
+
+    #[test]
+    fn tritone_alternation_is_no_cycle() {
+        let frames = [48, 54, 48, 54, 48].map(|root| triad(root, 4));
+        assert!(labels(&frames).is_empty());
+    }
+
+    #[test]
+    fn repeated_root_is_no_cycle() {
+        let frames = [4, 3, 4, 3, 4].map(|third| triad(48, third));
+        assert!(labels(&frames).is_empty());
+    }
+
+    #[test]
+    fn same_quality_thirds_are_a_chromatic_mediant_chain() {
+        let frames = [48, 52, 56, 60].map(|root| triad(root, 4));
+        assert_eq!(labels(&frames), ["chromatic mediant chain"]);
+    }
+}
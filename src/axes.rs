@@ -0,0 +1,168 @@
+use crate::hud::planar;
+use crate::rgba::{Palette, Rgb};
+use kiss3d::camera::Camera;
+use kiss3d::nalgebra::{Point2, Point3, Vector2, Vector3};
+use kiss3d::text::Font;
+use kiss3d::window::Window;
+use std::rc::Rc;
+
+const AXIS_COLORS: [(f32, f32, f32); 3] = [(0.9, 0.35, 0.35), (0.4, 0.85, 0.4), (0.4, 0.55, 1.0)];
+const AXIS_NAMES: [&str; 3] = ["x", "y", "z"];
+const AXIS_LENGTH: f32 = 400.0; // Scene distance from the origin to each labelled tip
+const TEXT_SIZE: f32 = 24.0;
+const CORNER_MARGIN: f32 = 20.0; // Distance of the gizmo and legend from the window edges
+const GIZMO_SIZE: f32 = 50.0; // Pixel length of each gizmo arm
+const LEGEND_WIDTH: f32 = 240.0; // Pixels
+const LEGEND_HEIGHT: f32 = 14.0;
+const ORIGIN_SCALE: f32 = 0.06; // Origin arrow length per unit of camera distance
+const ARROW_HEAD: f32 = 0.2; // Arrowhead length, as a share of the arrow
+const ORIGIN_MARK: f32 = 0.15; // Radius of the origin marker, as a share of the arrow
+
+// Scene axes through the origin, each labelled at its positive end with
+// what it measures
+pub fn draw_axes(window: &mut Window, camera: &dyn Camera, font: &Rc<Font>, names: &[String; 3]) {
+    let size = Vector2::new(window.width() as f32, window.height() as f32);
+    for (i, name) in names.iter().enumerate() {
+        let (r, g, b) = AXIS_COLORS[i];
+        let color = Point3::new(r, g, b);
+        let tip = Point3::from(Vector3::ith(i, AXIS_LENGTH));
+        window.draw_line(&Point3::origin(), &tip, &color);
+
+        // Skip labels behind the camera, which would project mirrored
+        if camera.view_transform().transform_point(&tip).z >= 0.0 {
+            continue;
+        }
+        let screen = camera.project(&tip, &size);
+        let at = Point2::new(screen.x, size.y - screen.y);
+        let label = format!("{}: {name}", AXIS_NAMES[i]);
+        window.draw_text(&label, &at, TEXT_SIZE, font, &color);
+    }
+}
+
+// Short arrows along x, y and z with a diamond where they meet, resized with
+// the camera distance so they keep their size on screen at any zoom
+pub fn draw_origin(window: &mut Window, camera: &dyn Camera, (r, g, b): Rgb) {
+    let length = arrow_length(camera);
+    let origin = Point3::origin();
+    for (i, &(r, g, b)) in AXIS_COLORS.iter().enumerate() {
+        let color = Point3::new(r, g, b);
+        let tip = Point3::from(Vector3::ith(i, length));
+        window.draw_line(&origin, &tip, &color);
+
+        // Head of four barbs back from the tip, across the other two axes
+        let back = tip * (1.0 - ARROW_HEAD);
+        for j in (0..3).filter(|&j| j != i) {
+            for side in [-1.0, 1.0] {
+                let barb = back + Vector3::ith(j, side * length * ARROW_HEAD / 2.0);
+                window.draw_line(&tip, &barb, &color);
+            }
+        }
+    }
+
+    // Octahedron outline: each corner joined to the four on other axes
+    let radius = origin_radius(camera);
+    let color = Point3::new(r, g, b);
+    let corners: Vec<Point3<f32>> = (0..3)
+        .flat_map(|i| [-radius, radius].map(|side| Point3::from(Vector3::ith(i, side))))
+        .collect();
+    for (a, from) in corners.iter().enumerate() {
+        for (b, to) in corners.iter().enumerate().skip(a + 1) {
+            if a / 2 != b / 2 {
+                window.draw_line(from, to, &color);
+            }
+        }
+    }
+}
+
+// Which way the scene axes point from the current view, in the bottom-left
+// corner; `lift` raises it clear of anything along the bottom edge
+pub fn draw_gizmo(window: &mut Window, camera: &dyn Camera, font: &Rc<Font>, lift: f32) {
+    let height = window.height() as f32;
+    let center = Point2::new(
+        CORNER_MARGIN + GIZMO_SIZE,
+        height - CORNER_MARGIN - lift - GIZMO_SIZE,
+    );
+    let rotation = camera.view_transform().rotation;
+    for i in 0..3 {
+        let (r, g, b) = AXIS_COLORS[i];
+        let color = Point3::new(r, g, b);
+        let arm = rotation * Vector3::ith(i, GIZMO_SIZE);
+        let end = Point2::new(center.x + arm.x, center.y - arm.y);
+        window.draw_planar_line(
+            &planar(window, center.x, center.y),
+            &planar(window, end.x, end.y),
+            &color,
+        );
+        window.draw_text(AXIS_NAMES[i], &end, TEXT_SIZE, font, &color);
+    }
+}
+
+// Bar of the hue cycle in the bottom-right corner, through the palette in
+// use, captioned with the motion component that drives it and how much of
+// it one cycle takes
+pub fn draw_legend(
+    window: &mut Window,
+    font: &Rc<Font>,
+    name: &str,
+    scale: f32,
+    palette: Palette,
+    lift: f32,
+) {
+    let (width, height) = (window.width() as f32, window.height() as f32);
+    let left = width - CORNER_MARGIN - LEGEND_WIDTH;
+    let bottom = height - CORNER_MARGIN - lift;
+    for x in 0..LEGEND_WIDTH as usize {
+        let (r, g, b) = palette.at(x as f32 / LEGEND_WIDTH);
+        let x = left + x as f32;
+        window.draw_planar_line(
+            &planar(window, x, bottom),
+            &planar(window, x, bottom - LEGEND_HEIGHT),
+            &Point3::new(r, g, b),
+        );
+    }
+
+    let caption = format!("hue: {name}, one cycle per {:.0}", 1.0 / scale);
+    window.draw_text(
+        &caption,
+        &Point2::new(left, bottom - LEGEND_HEIGHT - TEXT_SIZE - 4.0),
+        TEXT_SIZE,
+        font,
+        &Point3::new(0.85, 0.85, 0.9),
+    );
+}
+
+// Swatch and name of each color in use, stacked up from the bottom-right
+// corner, for hues that stand for categories rather than a cycle
+pub fn draw_key(window: &mut Window, font: &Rc<Font>, entries: &[(&str, Rgb)], lift: f32) {
+    let (width, height) = (window.width() as f32, window.height() as f32);
+    let left = width - CORNER_MARGIN - LEGEND_WIDTH;
+    for (row, &(name, (r, g, b))) in entries.iter().rev().enumerate() {
+        let bottom = height - CORNER_MARGIN - lift - row as f32 * (TEXT_SIZE + 4.0);
+        let color = Point3::new(r, g, b);
+        for y in 0..LEGEND_HEIGHT as usize {
+            let y = bottom - TEXT_SIZE / 2.0 + LEGEND_HEIGHT / 2.0 - y as f32;
+            window.draw_planar_line(
+                &planar(window, left, y),
+                &planar(window, left + LEGEND_HEIGHT, y),
+                &color,
+            );
+        }
+        window.draw_text(
+            name,
+            &Point2::new(left + LEGEND_HEIGHT + 8.0, bottom - TEXT_SIZE),
+            TEXT_SIZE,
+            font,
+            &color,
+        );
+    }
+}
+
+// Length of the origin arrows, seen from `camera`
+fn arrow_length(camera: &dyn Camera) -> f32 {
+    camera.eye().coords.norm() * ORIGIN_SCALE
+}
+
+// Radius of the origin marker, seen from `camera`
+pub fn origin_radius(camera: &dyn Camera) -> f32 {
+    arrow_length(camera) * ORIGIN_MARK
+}
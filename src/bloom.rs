@@ -0,0 +1,110 @@
+use kiss3d::context::Context;
+use kiss3d::nalgebra::Vector2;
+use kiss3d::post_processing::PostProcessingEffect;
+use kiss3d::resource::{
+    AllocationType, BufferType, Effect, GPUVec, RenderTarget, ShaderAttribute, ShaderUniform,
+};
+
+const THRESHOLD: f32 = 0.35; // Brightness above which pixels start to glow
+const STRENGTH: f32 = 1.4; // How much of the blurred glow is added back
+const RADIUS: f32 = 6.0; // Spacing of the blur taps, in pixels
+
+// Post-processing glow: bright pixels are blurred and added back on top,
+// so the sphere and trail bleed light into the dark background
+pub struct Bloom {
+    shader: Effect,
+    fbo_texture: ShaderUniform<i32>,
+    texel: ShaderUniform<Vector2<f32>>, // Size of one tap step in texture coordinates
+    v_coord: ShaderAttribute<Vector2<f32>>,
+    fbo_vertices: GPUVec<Vector2<f32>>, // Screen-filling quad
+    step: Vector2<f32>,
+}
+
+impl Bloom {
+    // Needs the window's GL context, so create it after opening the window
+    pub fn new() -> Bloom {
+        let quad = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+        let mut fbo_vertices = GPUVec::new(quad, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let fragment = FRAGMENT_SHADER
+            .replace("THRESHOLD", &format!("{THRESHOLD:.3}"))
+            .replace("STRENGTH", &format!("{STRENGTH:.3}"));
+        let mut shader = Effect::new_from_str(VERTEX_SHADER, &fragment);
+        shader.use_program();
+
+        Bloom {
+            fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            texel: shader.get_uniform("texel").unwrap(),
+            v_coord: shader.get_attrib("v_coord").unwrap(),
+            fbo_vertices,
+            shader,
+            step: Vector2::new(RADIUS / 800.0, RADIUS / 600.0),
+        }
+    }
+}
+
+impl PostProcessingEffect for Bloom {
+    fn update(&mut self, _: f32, width: f32, height: f32, _: f32, _: f32) {
+        self.step = Vector2::new(RADIUS / width.max(1.0), RADIUS / height.max(1.0));
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+        self.v_coord.enable();
+        self.shader.use_program();
+        ctxt.clear_color(0.0, 0.0, 0.0, 1.0);
+        ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT);
+        ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id());
+
+        self.fbo_texture.upload(&0);
+        self.texel.upload(&self.step);
+        self.v_coord.bind(&mut self.fbo_vertices);
+        ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4);
+
+        self.v_coord.disable();
+    }
+}
+
+static VERTEX_SHADER: &str = "#version 100
+    attribute vec2 v_coord;
+    varying vec2   f_texcoord;
+
+    void main(void) {
+      gl_Position = vec4(v_coord, 0.0, 1.0);
+      f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+// Two rings of taps approximate a gaussian blur of the bright parts
+static FRAGMENT_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    uniform vec2      texel;
+    varying vec2      f_texcoord;
+
+    vec3 bright(vec2 at) {
+      return max(texture2D(fbo_texture, at).rgb - THRESHOLD, 0.0);
+    }
+
+    void main(void) {
+      vec4 color = texture2D(fbo_texture, f_texcoord);
+      vec3 glow  = bright(f_texcoord) * 0.2;
+      for (int i = 0; i < 8; i++) {
+        float angle = float(i) * 0.785398;
+        vec2 dir    = vec2(cos(angle), sin(angle)) * texel;
+        glow += bright(f_texcoord + dir) * 0.06;
+        glow += bright(f_texcoord + dir * 2.5) * 0.04;
+      }
+      gl_FragColor = vec4(color.rgb + glow * STRENGTH, color.a);
+    }";
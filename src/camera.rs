@@ -0,0 +1,284 @@
+use crate::json;
+use kiss3d::camera::{ArcBall, Camera};
+use kiss3d::nalgebra::{Point3, Vector3, Vector4};
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+use std::fs;
+use std::path::Path;
+
+const GLIDE_TIME: f32 = 0.6; // Seconds to move between views
+const FIT_MARGIN: f32 = 1.2; // Breathing room around a fitted trajectory
+const HALF_FOV: f32 = PI / 8.0; // Half of kiss3d's default 45° field of view
+const MIN_RADIUS: f32 = 50.0; // Smallest extent a fit zooms in to
+const ORBIT_SETTLE: f32 = 1.5; // Rate (1/s) at which the orbit eases onto its centre and elevation
+const FRAME_SETTLE: f32 = 1.0; // Rate (1/s) at which auto framing eases onto the recent trail
+
+// Orbit camera parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct View {
+    pub at: Point3<f32>, // Point the camera orbits
+    pub yaw: f32,        // Angle around the vertical axis
+    pub pitch: f32,      // Angle down from straight above
+    pub dist: f32,       // Distance from the orbited point
+}
+
+impl View {
+    // Current parameters of an orbit camera
+    pub fn of(camera: &ArcBall) -> Self {
+        Self {
+            at: camera.at(),
+            yaw: camera.yaw(),
+            pitch: camera.pitch(),
+            dist: camera.dist(),
+        }
+    }
+
+    pub fn apply(&self, camera: &mut ArcBall) {
+        camera.set_at(self.at);
+        camera.set_yaw(self.yaw);
+        camera.set_pitch(self.pitch);
+        camera.set_dist(self.dist);
+    }
+
+    // Blend towards another view, turning the short way round
+    pub fn lerp(&self, to: &View, t: f32) -> View {
+        let turn = (to.yaw - self.yaw + PI).rem_euclid(2.0 * PI) - PI;
+        View {
+            at: self.at + (to.at - self.at) * t,
+            yaw: self.yaw + turn * t,
+            pitch: self.pitch + (to.pitch - self.pitch) * t,
+            dist: self.dist + (to.dist - self.dist) * t,
+        }
+    }
+}
+
+// Canonical view bound to a number key (1-6), framing the given trajectory
+pub fn preset(number: u8, current: View, path: &[Point3<f32>], home: View) -> Option<View> {
+    let (center, radius) = bounds(path);
+    let view = |yaw, pitch| View {
+        at: center,
+        yaw,
+        pitch,
+        dist: current.dist,
+    };
+    Some(match number {
+        1 => view(-FRAC_PI_2, 0.01),                        // Top-down
+        2 => view(FRAC_PI_2, FRAC_PI_2),                    // Front, looking down -z
+        3 => view(0.0, FRAC_PI_2),                          // Side, looking down -x
+        4 => view(FRAC_PI_4, (1.0f32 / 3.0).sqrt().acos()), // Isometric
+        5 => home,                                          // Start view around the origin
+        6 => View {
+            dist: radius * FIT_MARGIN / HALF_FOV.sin(),
+            ..view(current.yaw, current.pitch)
+        },
+        _ => return None,
+    })
+}
+
+// Center and radius of a sphere enclosing the trajectory
+fn bounds(path: &[Point3<f32>]) -> (Point3<f32>, f32) {
+    let Some(first) = path.first() else {
+        return (Point3::origin(), MIN_RADIUS);
+    };
+    let (mut lo, mut hi) = (*first, *first);
+    for p in path {
+        for axis in 0..3 {
+            lo[axis] = lo[axis].min(p[axis]);
+            hi[axis] = hi[axis].max(p[axis]);
+        }
+    }
+    let center = Point3::from((lo.coords + hi.coords) / 2.0);
+    let radius = path
+        .iter()
+        .map(|p| (p - center).norm())
+        .fold(MIN_RADIUS, f32::max);
+    (center, radius)
+}
+
+// Mean position of the trajectory's keyframes
+fn centroid(path: &[Point3<f32>]) -> Point3<f32> {
+    let sum = path.iter().fold(Vector3::zeros(), |sum, p| sum + p.coords);
+    Point3::from(sum / path.len().max(1) as f32)
+}
+
+// Slow unattended turn around the trajectory's centroid at a fixed
+// elevation; zooming still works, and a drag only adds to the turn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orbit {
+    pub speed: f32,     // Radians per second around the vertical axis
+    pub elevation: f32, // Radians above the horizon
+}
+
+impl Orbit {
+    pub fn step(&self, camera: &mut ArcBall, path: &[Point3<f32>], delta_time: f32) {
+        let settle = (ORBIT_SETTLE * delta_time).min(1.0);
+        let at = camera.at();
+        camera.set_at(at + (centroid(path) - at) * settle);
+        let pitch = camera.pitch();
+        camera.set_pitch(pitch + (FRAC_PI_2 - self.elevation - pitch) * settle);
+        camera.set_yaw(camera.yaw() + self.speed * delta_time);
+    }
+}
+
+// Smart framing: keeps the sphere and the trail of the last few seconds in
+// view by easing the orbited point and distance onto them; turning the
+// view by hand still works
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Framing {
+    pub seconds: f32, // Piece time of trail kept in the frame
+}
+
+impl Framing {
+    pub fn step(&self, camera: &mut ArcBall, recent: &[Point3<f32>], delta_time: f32) {
+        let (center, radius) = bounds(recent);
+        let settle = (FRAME_SETTLE * delta_time).min(1.0);
+        let at = camera.at();
+        camera.set_at(at + (center - at) * settle);
+        let dist = camera.dist();
+        let fit = radius * FIT_MARGIN / HALF_FOV.sin();
+        camera.set_dist(dist + (fit - dist) * settle);
+    }
+}
+
+// Eased move of the orbit camera from one view to another
+pub struct Glide {
+    from: View,
+    to: View,
+    elapsed: f32,
+}
+
+impl Glide {
+    pub fn new(from: View, to: View) -> Self {
+        Self {
+            from,
+            to,
+            elapsed: 0.0,
+        }
+    }
+
+    // Advance the move; returns false once the camera has arrived
+    pub fn step(&mut self, camera: &mut ArcBall, delta_time: f32) -> bool {
+        self.elapsed += delta_time;
+        let t = (self.elapsed / GLIDE_TIME).min(1.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+        self.from.lerp(&self.to, eased).apply(camera);
+        t < 1.0
+    }
+}
+
+// Orbit camera views over piece time, recorded with Shift+R and replayed
+// with --camera-path, so a fly-through can be repeated for a recording
+#[derive(Default)]
+pub struct CameraPath {
+    samples: Vec<(f32, View)>, // Piece time in seconds with the view then, oldest first
+}
+
+impl CameraPath {
+    // Add the view at a piece time; while time stands still (paused) the
+    // newest view replaces the last one, and time going back is ignored
+    pub fn record(&mut self, time: f32, view: View) {
+        match self.samples.last_mut() {
+            Some((last, _)) if time < *last => {}
+            Some((last, latest)) if time == *last => *latest = view,
+            _ => self.samples.push((time, view)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    // View at a piece time, blended between the samples around it and
+    // held before the first and after the last
+    pub fn view_at(&self, time: f32) -> Option<View> {
+        let next = self.samples.partition_point(|&(t, _)| t <= time);
+        match (
+            self.samples.get(next.wrapping_sub(1)),
+            self.samples.get(next),
+        ) {
+            (Some(&(t0, v0)), Some(&(t1, v1))) => Some(v0.lerp(&v1, (time - t0) / (t1 - t0))),
+            (Some(&(_, view)), None) | (None, Some(&(_, view))) => Some(view),
+            (None, None) => None,
+        }
+    }
+
+    // Write the samples as JSON rows of [time, at x, at y, at z, yaw, pitch, dist]
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let rows: Vec<String> = self
+            .samples
+            .iter()
+            .map(|(t, v)| {
+                format!(
+                    "    [{t}, {}, {}, {}, {}, {}, {}]",
+                    v.at.x, v.at.y, v.at.z, v.yaw, v.pitch, v.dist
+                )
+            })
+            .collect();
+        fs::write(
+            path,
+            format!("{{\n  \"camera\": [\n{}\n  ]\n}}\n", rows.join(",\n")),
+        )
+    }
+
+    pub fn load(path: &Path) -> Result<CameraPath, Box<dyn std::error::Error>> {
+        let text = fs::read_to_string(path)?;
+        let doc = json::parse(&text)?;
+        let rows = doc
+            .get("camera")
+            .and_then(json::Value::as_array)
+            .ok_or("expected an object with a \"camera\" array")?;
+
+        let mut camera = CameraPath::default();
+        for (i, row) in rows.iter().enumerate() {
+            let numbers: Option<Vec<f32>> = row
+                .as_array()
+                .map(|r| r.iter().map(|v| v.as_f64().map(|n| n as f32)).collect())
+                .unwrap_or(None);
+            match numbers.as_deref() {
+                Some(&[time, x, y, z, yaw, pitch, dist]) => camera.record(
+                    time,
+                    View {
+                        at: Point3::new(x, y, z),
+                        yaw,
+                        pitch,
+                        dist,
+                    },
+                ),
+                _ => return Err(format!("camera sample {i} is not 7 numbers").into()),
+            }
+        }
+        Ok(camera)
+    }
+}
+
+// The six planes bounding what a camera sees, pointing inwards, taken
+// from its combined projection and view matrix
+pub struct Frustum {
+    planes: [Vector4<f32>; 6],
+    pub eye: Point3<f32>,
+}
+
+impl Frustum {
+    pub fn of(camera: &dyn Camera) -> Frustum {
+        let m = camera.transformation();
+        let row = |i: usize| m.row(i).transpose();
+        let planes = [
+            row(3) + row(0),
+            row(3) - row(0),
+            row(3) + row(1),
+            row(3) - row(1),
+            row(3) + row(2),
+            row(3) - row(2),
+        ];
+        Frustum {
+            planes: planes.map(|p| p / p.xyz().norm().max(1e-9)),
+            eye: camera.eye(),
+        }
+    }
+
+    // Whether any of the sphere can be in view
+    pub fn sees(&self, center: &Point3<f32>, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|p| p.xyz().dot(&center.coords) + p.w >= -radius)
+    }
+}
@@ -0,0 +1,157 @@
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::{self, FilterType};
+use image::{Delay, DynamicImage, Frame};
+use kiss3d::window::Window;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+// Collects rendered frames into a video through ffmpeg, into a numbered
+// PNG sequence when the output is a .png name or ffmpeg is unavailable, or
+// into an animated GIF
+pub struct Recorder {
+    path: PathBuf,                       // Video file, or pattern for the PNG frames
+    fps: u32,                            // Frame rate the video is encoded at
+    frames: usize,                       // Frames captured so far
+    png: bool,                           // Writing PNG frames instead of piping to ffmpeg
+    ffmpeg: Option<(Child, (u32, u32))>, // Running encoder and the frame size it expects
+    gif_size: Option<(u32, u32)>,        // Collecting GIF frames scaled to this size
+    gif_frames: Vec<Frame>,              // GIF frames, encoded once recording ends
+}
+
+impl Recorder {
+    pub fn new(path: &Path, fps: u32) -> Self {
+        let png = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+        Self {
+            path: path.to_path_buf(),
+            fps,
+            frames: 0,
+            png,
+            ffmpeg: None,
+            gif_size: None,
+            gif_frames: Vec::new(),
+        }
+    }
+
+    // Record an animated GIF with frames scaled to the given size
+    pub fn gif(path: &Path, fps: u32, size: (u32, u32)) -> Self {
+        Self {
+            gif_size: Some(size),
+            ..Self::new(path, fps)
+        }
+    }
+
+    // Write numbered PNG frames into a directory, creating it if needed
+    pub fn sequence(dir: &Path, fps: u32) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        Ok(Self {
+            png: true,
+            ..Self::new(&dir.join("frame.png"), fps)
+        })
+    }
+
+    // Grab the frame last shown in the window
+    pub fn frame(&mut self, window: &Window) -> Result<(), String> {
+        let image = window.snap_image();
+        let size = (image.width(), image.height());
+        self.frames += 1;
+
+        if let Some((width, height)) = self.gif_size {
+            let rgba = DynamicImage::ImageRgb8(image).into_rgba8();
+            let scaled = imageops::resize(&rgba, width, height, FilterType::Triangle);
+            let delay = Delay::from_numer_denom_ms(1000, self.fps);
+            self.gif_frames.push(Frame::from_parts(scaled, 0, 0, delay));
+            return Ok(());
+        }
+
+        // The encoder needs the frame size, so start it with the first frame
+        if !self.png && self.ffmpeg.is_none() {
+            match self.spawn(size) {
+                Ok(child) => self.ffmpeg = Some((child, size)),
+                Err(e) => {
+                    eprintln!("[-.-] Could not start ffmpeg ({e}), writing PNG frames instead");
+                    self.png = true;
+                }
+            }
+        }
+
+        match &mut self.ffmpeg {
+            Some((child, expected)) if !self.png => {
+                if size != *expected {
+                    return Err("the window was resized during capture".into());
+                }
+                let stdin = child.stdin.as_mut().ok_or("ffmpeg input is closed")?;
+                stdin
+                    .write_all(image.as_raw())
+                    .map_err(|e| format!("ffmpeg stopped accepting frames: {e}"))
+            }
+            _ => {
+                let path = frame_path(&self.path, self.frames);
+                image
+                    .save(&path)
+                    .map_err(|e| format!("{}: {e}", path.display()))
+            }
+        }
+    }
+
+    // Close the encoder and report where the frames went
+    pub fn finish(mut self) -> Result<String, String> {
+        if self.gif_size.is_some() {
+            let file =
+                File::create(&self.path).map_err(|e| format!("{}: {e}", self.path.display()))?;
+            let mut encoder = GifEncoder::new(BufWriter::new(file));
+            encoder
+                .set_repeat(Repeat::Infinite)
+                .and_then(|()| encoder.encode_frames(self.gif_frames))
+                .map_err(|e| format!("{}: {e}", self.path.display()))?;
+            return Ok(format!("{} frames to {}", self.frames, self.path.display()));
+        }
+        if let Some((mut child, _)) = self.ffmpeg.take() {
+            drop(child.stdin.take());
+            let status = child.wait().map_err(|e| e.to_string())?;
+            if !status.success() {
+                return Err(format!("ffmpeg exited with {status}"));
+            }
+            return Ok(format!("{} frames to {}", self.frames, self.path.display()));
+        }
+        Ok(format!(
+            "{} frames to {} ...",
+            self.frames,
+            frame_path(&self.path, 1).display()
+        ))
+    }
+
+    fn spawn(&self, (width, height): (u32, u32)) -> std::io::Result<Child> {
+        Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgb24"])
+            .args(["-s", &format!("{width}x{height}")])
+            .args(["-r", &self.fps.to_string(), "-i", "-"])
+            // Most players need yuv420p, which needs even dimensions
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+            .args(["-pix_fmt", "yuv420p"])
+            .arg(&self.path)
+            .stdin(Stdio::piped())
+            .spawn()
+    }
+}
+
+// Save the frame last shown in the window as a single image
+pub fn still(window: &Window, path: &Path) -> Result<(), String> {
+    window
+        .snap_image()
+        .save(path)
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+// Numbered frame name derived from the output path: out.png -> out-000001.png
+fn frame_path(path: &Path, frame: usize) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "frame".into());
+    path.with_file_name(format!("{stem}-{frame:06}.png"))
+}
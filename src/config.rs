@@ -0,0 +1,804 @@
+use crate::easing::Easing;
+use crate::glyph::Glyph;
+use crate::mapping;
+use crate::midi::{Fill, KeyframeMode};
+use crate::projection::{Projection, Scale};
+use crate::rgba::{self, Coloring, Palette, Rgb};
+use crate::shading::Finish;
+use crate::smoothing::Smoothing;
+use crate::theme::{self, Lighting, Theme};
+use crate::trail::Trail;
+use crate::transformation::{self, Matrix, Metric};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const POSTER_SIZE: (u32, u32) = (2400, 1600); // Poster size unless --width/--height are given
+
+pub const USAGE: &str = "Usage: ./visual inspect <path-to-midi-file>
+       ./visual <midi-file|folder|playlist> [--cues <cue-file>] [--sequence <n>]
+       [--fill bass-octave|soprano|collapse]   (completes pieces with fewer than four voices, default bass-octave)
+       [--analysis <report-file>]
+       [--keyframes grid|events] [--isocontours] [--pitch-class]
+       [--mapping <name>] [--orbifold] [--absolute]
+       [--projection drop-w|perspective|stereographic|pca] [--rotate <rad-per-s>]
+       [--smooth average:<window>|exp:<alpha>] [--weights <w,x,y,z>]
+       [--trail-metric l1|l2|linf] [--trail last:<n>|all|decay:<seconds>|piece]
+       [--ghost]   (the whole path drawn dim from the start, lit as the sphere reaches it, toggle with F2)
+       [--per-beat]
+       [--transposition-invariant]
+       [--orientation]   (turn the sphere by each motion read as a quaternion and show its axes, toggle with O)
+       [--labels] [--label-spacing <pixels>] [--voices] [--particles]
+       [--bloom] [--axes] [--shadow] [--tube] [--piano-roll] [--downbeats] [--markers] [--loop] [--keep-trail]
+       [--panel]   (control panel, toggle with Tab)
+       [--step]   (start in step mode: N and P move one keyframe)
+       [--glyph sphere|tetrahedron|both] [--voice-palette distinct|primary|pastel|colorblind]
+       [--palette rainbow|viridis|plasma|tension|tension-bright|cividis]   (colors of the hue cycle)
+       [--color-by motion|quality]   (hue from total motion or from the chord's quality, toggle with C)
+       [--pulse]   (sphere swells with the total motion of each step)
+       [--emissive <0-1>] [--sphere-opacity <0-1>] [--wireframe]   (sphere glowing in its own hue, see-through, or wire only, toggle with F5)
+       [--slice]   (cross-section of the 4D path by a hyperplane of constant w, toggle with X, move with , and .)
+       [--colorblind]   (cividis hues and colorblind voice colors, over both palette options)
+       [--easing linear|smoothstep|cubic|exponential] [--speed <0.1-8>] [--render-video <out.mp4|frames.png>] [--fps <n>]
+       [--export-gif <out.gif>] [--gif-size <width>x<height>] [--bars <first>-<last>]
+       [--export-frames <dir>]   (frame-000001.png ... at --fps and the window size, for your own video tools)
+       [--poster <out.png>]   (one labelled still of the whole piece, fitted to the window, then exit; 2400x1600 by default)
+       [--camera-path <file.camera.json>]   (replay a camera path recorded with Shift+R)
+       [--midi-out <port>]   (sound each chord on a raw MIDI port such as /dev/snd/midiC1D0, following pause and seeks)
+       [--stats]   (frame time, scene node and vertex counts, toggle with F3)
+       [--hidden]   (render without showing the window, for recording)
+       [--width <pixels>] [--height <pixels>] [--no-vsync]
+       [--msaa 0|2|4|8|16] [--smooth-lines]   (antialiasing; bloom and anaglyph render unsampled)
+       [--theme dark|light|high-contrast] [--background <r,g,b>] [--grid-color <r,g,b>]
+       [--no-grid] [--grid-size <units>] [--light camera|above]   (colors in 0..1, override the theme)
+       [--key-light <0-4>] [--fill-light <0-4>] [--sphere-light <0-4>]   (light intensities over the theme's; the sphere lights nearby trail)
+       [--position-scale <units>|auto]   (scene units per 100 semitones, default 1000; auto fits the path to the grid)
+       [--grid-cells <count>]   (cells from the origin to each grid edge; Q toggles, F7/F8 cell size, F9/F10 extent)
+       [--orbit] [--orbit-speed <degrees/s>] [--orbit-elevation <degrees>]   (unattended camera turn, toggle with W)
+       [--auto-frame] [--frame-seconds <seconds>]   (keep the sphere and that much recent trail in view, default 8; toggle with F4)
+       [--anaglyph]   (red/cyan stereo for glasses, toggle with Y)
+       [--sky]   (the theme's gradient sky instead of a flat background)
+       [--config <file>]   (file lines: option = value, [section] headers group them)
+       [--reference <path.json>]... [--export-path <path.json>]
+       [--overlay <a.mid> <b.mid>...]   (whole paths drawn still in distinct colors; alone, the first becomes the piece, paused)
+       [--paused]   (start with playback paused, Space plays)
+       [--export-mesh <out.obj|out.ply>] [--mesh-radius <r>]   (0 writes a polyline)
+       [--energy] [--basis contrary|hadamard|satb-pairs|register-spread]
+       [--matrix <rows|file>]   e.g. --matrix \"1,1,1,1; 1,-1,-1,1; 1,-1,1,-1; 1,1,-1,-1\"";
+
+// Command line configuration
+pub struct Config {
+    pub path: PathBuf,                 // MIDI file, folder or playlist to visualize
+    pub cues: Option<PathBuf>,         // Optional pacing cue file
+    pub sequence: Option<usize>,       // Type 2 files: play only this sequence (1-based)
+    pub fill: Fill,                    // Strategy for pieces with fewer than four voices
+    pub analysis: Option<PathBuf>,     // Where to write the analysis report
+    pub keyframes: KeyframeMode,       // Fixed 16th grid or one keyframe per change
+    pub isocontours: bool,             // Show voice-leading distance shells at start
+    pub pitch_class: bool,             // Fold voices mod 12 before transformation
+    pub mapping: String,               // Name of the chord-space mapping
+    pub absolute: bool,                // Start at the first chord's offset from middle C
+    pub references: Vec<PathBuf>,      // Reference paths to overlay
+    pub overlay: Vec<PathBuf>,         // Pieces drawn whole as still paths
+    pub paused: bool,                  // Start with playback paused
+    pub export_path: Option<PathBuf>,  // Where to write this piece's keyframe path
+    pub matrix: Matrix,                // Voice-motion decomposition basis
+    pub energy: bool,                  // Show the energy graph at start
+    pub projection: Projection,        // Initial 4D to 3D projection
+    pub rotation_speed: f32,           // Initial 4D rotation speed (rad/s)
+    pub smoothing: Option<Smoothing>,  // Filter applied to motion vectors before rendering
+    pub weights: [f32; 4],             // Scale of the total, x, y and z axes
+    pub trail_metric: Option<Metric>,  // Voice-leading size driving trail brightness
+    pub trail: Trail,                  // How much of the path stays drawn
+    pub per_beat: bool,                // Normalize motion by its length in beats
+    pub transposition_invariant: bool, // Remove the mean voice motion from each step
+    pub orientation: bool,             // Rotate the sphere by the motion quaternion
+    pub speed: f32,                    // Initial playback speed multiplier
+    pub easing: Easing,                // Shape of the move between keyframes
+    pub labels: bool,                  // Show chord names along the path at start
+    pub label_spacing: f32,            // Minimum screen distance between labels (pixels)
+    pub voices: bool,                  // Show one sphere per voice at start
+    pub particles: bool,               // Burst particles when a keyframe starts
+    pub bloom: bool,                   // Glow post-processing at start
+    pub axes: bool,                    // Show labelled axes, gizmo and hue legend at start
+    pub shadow: bool,                  // Show the trail's shadow on the grid plane at start
+    pub ghost: bool,                   // Show the whole path dimmed ahead of the sphere at start
+    pub tube: bool,                    // Wrap the trail in a tube sized by voice leading
+    pub piano_roll: bool,              // Show the four voices as a piano roll at start
+    pub stats: bool,                   // Show the performance stats overlay at start
+    pub looping: bool,                 // Restart from the first keyframe at the end
+    pub keep_trail: bool,              // Keep the previous pass drawn when looping
+    pub step: bool,                    // Start in step mode, one keyframe per key press
+    pub panel: bool,                   // Show the control panel at start
+    pub glyph: Glyph,                  // Initial marker of the current chord
+    pub voice_colors: [Rgb; 4],        // Color of each voice in per-voice visuals
+    pub palette: Palette,              // Colors the hue of each motion is mapped through
+    pub coloring: Coloring,            // What the hue encodes
+    pub pulse: bool,                   // Scale the sphere by each step's total motion
+    pub finish: Finish,                // Emission and opacity of the sphere's surface
+    pub wireframe: bool,               // Draw the sphere as wire only at start
+    pub slice: bool,                   // Start showing a 4D cross-section instead of the trail
+    pub downbeats: bool,               // Mark bar lines along the trail
+    pub markers: bool,                 // Leave a cube at every keyframe reached
+    pub camera_path: Option<PathBuf>,  // Recorded camera path to replay
+    pub midi_out: Option<PathBuf>,     // Raw MIDI port the chords are sounded on
+    pub anaglyph: bool,                // Render red/cyan stereo
+    pub orbit: bool,                   // Start with the camera orbiting the trajectory
+    pub orbit_speed: f32,              // Orbit speed (degrees per second)
+    pub orbit_elevation: f32,          // Orbit height above the horizon (degrees)
+    pub auto_frame: bool,              // Start with the camera framing the recent trail
+    pub frame_seconds: f32,            // Piece time of trail kept in view when framing
+    pub render_video: Option<PathBuf>, // Record the animation to this video or PNG sequence
+    pub fps: u32,                      // Frame rate of recorded output
+    pub hidden: bool,                  // Render into an invisible window
+    pub window_size: (u32, u32),       // Window width and height (logical pixels)
+    pub vsync: bool,                   // Wait for the display between frames
+    pub msaa: u32,                     // Multisamples per pixel, 0 for none
+    pub smooth_lines: bool,            // Antialias lines with GL line smoothing
+    pub export_gif: Option<PathBuf>,   // Record the animation to an animated GIF
+    pub frame_dir: Option<PathBuf>,    // Record the animation as PNG frames in this directory
+    pub poster: Option<PathBuf>,       // Save a still of the whole piece here and exit
+    pub gif_size: (u32, u32),          // Width and height of GIF frames
+    pub bars: Option<(usize, usize)>,  // Only record this bar range (1-based, inclusive)
+    pub export_mesh: Option<PathBuf>,  // Where to write the trajectory as OBJ or PLY
+    pub mesh_radius: f32,              // Tube radius of the exported mesh (0: polyline)
+    pub theme: Theme,                  // Background, grid, text and lighting
+    pub sky: bool,                     // Draw the theme's gradient sky
+    pub grid_size: f32,                // Side of one reference grid cell (scene units)
+    pub grid_cells: u32,               // Cells from the origin to each edge of the grid
+    pub position_scale: Scale,         // Scene distance per semitone of motion
+}
+
+// Turn a config file into the equivalent command line arguments. Each
+// line is "option = value" using the long option names without "--";
+// "true" switches a flag on, "false" leaves it off, "path" is the input,
+// and "[section]" headers only group related options.
+fn file_args(path: &Path) -> Result<(Vec<String>, Option<PathBuf>), String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut args = Vec::new();
+    let mut input = None;
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() || (line.starts_with('[') && line.ends_with(']')) {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .map(|(k, v)| (k.trim(), v.trim()))
+            .ok_or(format!("config line {}: expected option = value", n + 1))?;
+        match (key, value) {
+            ("path", value) => input = Some(PathBuf::from(value)),
+            (_, "false") => {}
+            (key, "true") => args.push(format!("--{key}")),
+            (key, value) => args.extend([format!("--{key}"), value.to_string()]),
+        }
+    }
+    Ok((args, input))
+}
+
+// Parse "r,g,b" with each channel in 0..1
+fn parse_color(value: &str) -> Option<Rgb> {
+    let parts: Vec<f32> = value
+        .split(',')
+        .map(|v| v.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match parts[..] {
+        [r, g, b] if parts.iter().all(|c| (0.0..=1.0).contains(c)) => Some((r, g, b)),
+        _ => None,
+    }
+}
+
+// Parse "a,b,c,d" into four numbers
+fn parse_weights(value: &str) -> Option<[f32; 4]> {
+    let parts: Vec<f32> = value
+        .split(',')
+        .map(|v| v.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    parts.try_into().ok()
+}
+
+// Parse "a-b" (bar range) or "WxH" (frame size) into two numbers
+fn parse_pair<T: std::str::FromStr>(value: &str, separator: char) -> Option<(T, T)> {
+    let (a, b) = value.split_once(separator)?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+// Parse command line arguments (without the program name). Options from a
+// --config file come first, so the command line overrides them.
+pub fn parse_args(args: &[String]) -> Result<Config, String> {
+    let mut combined = Vec::new();
+    let mut rest = Vec::new();
+    let mut file_path = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--config" {
+            let value = iter.next().ok_or("--config expects a file path")?;
+            let (file_args, input) = file_args(Path::new(value))?;
+            combined.extend(file_args);
+            file_path = input.or(file_path);
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    combined.extend(rest);
+    let args = &combined;
+
+    let mut path = None;
+    let mut cues = None;
+    let mut sequence = None;
+    let mut fill = Fill::BassOctave;
+    let mut analysis = None;
+    let mut keyframes = KeyframeMode::Grid;
+    let mut isocontours = false;
+    let mut pitch_class = false;
+    let mut mapping = mapping::names()[0].to_string();
+    let mut references = Vec::new();
+    let mut overlay = Vec::new();
+    let mut paused = false;
+    let mut export_path = None;
+    let mut matrix = transformation::DEFAULT_BASIS;
+    let mut energy = false;
+    let mut absolute = false;
+    let mut projection = Projection::DropW;
+    let mut rotation_speed = 0.0;
+    let mut smoothing = None;
+    let mut weights = [1.0; 4];
+    let mut trail_metric = None;
+    let mut trail = Trail::Last(100);
+    let mut per_beat = false;
+    let mut transposition_invariant = false;
+    let mut orientation = false;
+    let mut speed = 1.0;
+    let mut easing = Easing::Smoothstep;
+    let mut labels = false;
+    let mut label_spacing = 60.0;
+    let mut voices = false;
+    let mut particles = false;
+    let mut bloom = false;
+    let mut axes = false;
+    let mut shadow = false;
+    let mut ghost = false;
+    let mut tube = false;
+    let mut piano_roll = false;
+    let mut stats = false;
+    let mut looping = false;
+    let mut keep_trail = false;
+    let mut step = false;
+    let mut panel = false;
+    let mut glyph = Glyph::Sphere;
+    let mut voice_colors = rgba::VOICE_PALETTES[0].1;
+    let mut palette = rgba::PALETTES[0].1;
+    let mut coloring = Coloring::Motion;
+    let mut pulse = false;
+    let mut finish = Finish::default();
+    let mut wireframe = false;
+    let mut slice = false;
+    let mut downbeats = false;
+    let mut markers = false;
+    let mut camera_path = None;
+    let mut midi_out = None;
+    let mut anaglyph = false;
+    let mut orbit = false;
+    let mut orbit_speed = 6.0;
+    let mut orbit_elevation = 25.0;
+    let mut auto_frame = false;
+    let mut frame_seconds = 8.0;
+    let mut theme = theme::THEMES[0].1;
+    let mut background = None;
+    let mut grid_color = None;
+    let mut no_grid = false;
+    let mut colorblind = false;
+    let mut lighting = None;
+    let mut intensities: [Option<f32>; 3] = [None; 3];
+    let mut sky = false;
+    let mut grid_size = 200.0;
+    let mut grid_cells = 10;
+    let mut position_scale = Scale::Fixed(1000.0);
+    let mut render_video = None;
+    let mut fps = 30;
+    let mut hidden = false;
+    let mut window_size = (800, 600);
+    let mut sized = false; // --width or --height given
+    let mut vsync = true;
+    let mut msaa = 0;
+    let mut smooth_lines = false;
+    let mut export_gif = None;
+    let mut frame_dir = None;
+    let mut poster = None;
+    let mut gif_size = (480, 360);
+    let mut bars = None;
+    let mut export_mesh = None;
+    let mut mesh_radius = 2.0;
+
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--cues" => {
+                let value = iter.next().ok_or("--cues expects a file path")?;
+                cues = Some(PathBuf::from(value));
+            }
+            "--sequence" => {
+                let value = iter.next().ok_or("--sequence expects a number")?;
+                match value.parse::<usize>() {
+                    Ok(n) if n >= 1 => sequence = Some(n),
+                    _ => return Err(format!("Invalid sequence number {value}")),
+                }
+            }
+            "--fill" => {
+                let value = iter.next().ok_or("--fill expects a strategy")?;
+                fill = Fill::from_name(value).ok_or(format!("Unknown fill strategy {value}"))?;
+            }
+            "--analysis" => {
+                let value = iter.next().ok_or("--analysis expects a file path")?;
+                analysis = Some(PathBuf::from(value));
+            }
+            "--keyframes" => {
+                let value = iter.next().ok_or("--keyframes expects grid or events")?;
+                keyframes = KeyframeMode::from_name(value)
+                    .ok_or(format!("Unknown keyframe mode {value}"))?;
+            }
+            "--isocontours" => isocontours = true,
+            "--pitch-class" => pitch_class = true,
+            "--mapping" => {
+                let value = iter.next().ok_or("--mapping expects a mapping name")?;
+                if !mapping::names().contains(&value.as_str()) {
+                    return Err(format!(
+                        "Unknown mapping {value} (available: {})",
+                        mapping::names().join(", ")
+                    ));
+                }
+                mapping = value.clone();
+            }
+            "--orbifold" => mapping = "orbifold".to_string(),
+            "--absolute" => absolute = true,
+            "--projection" => {
+                let value = iter.next().ok_or("--projection expects a strategy")?;
+                projection =
+                    Projection::from_name(value).ok_or(format!("Unknown projection {value}"))?;
+            }
+            "--energy" => energy = true,
+            "--per-beat" => per_beat = true,
+            "--transposition-invariant" => transposition_invariant = true,
+            "--orientation" => orientation = true,
+            "--hidden" => hidden = true,
+            "--labels" => labels = true,
+            "--voices" => voices = true,
+            "--particles" => particles = true,
+            "--bloom" => bloom = true,
+            "--axes" => axes = true,
+            "--shadow" => shadow = true,
+            "--ghost" => ghost = true,
+            "--tube" => tube = true,
+            "--piano-roll" => piano_roll = true,
+            "--stats" => stats = true,
+            "--loop" => looping = true,
+            "--keep-trail" => keep_trail = true,
+            "--step" => step = true,
+            "--panel" => panel = true,
+            "--voice-palette" => {
+                let value = iter
+                    .next()
+                    .ok_or("--voice-palette expects a palette name")?;
+                voice_colors =
+                    rgba::voice_palette(value).ok_or(format!("Unknown voice palette {value}"))?;
+            }
+            "--palette" => {
+                let value = iter.next().ok_or("--palette expects a palette name")?;
+                palette = rgba::palette(value).ok_or(format!("Unknown palette {value}"))?;
+            }
+            "--color-by" => {
+                let value = iter.next().ok_or("--color-by expects motion or quality")?;
+                coloring = Coloring::from_name(value).ok_or(format!("Unknown coloring {value}"))?;
+            }
+            "--theme" => {
+                let value = iter.next().ok_or("--theme expects a theme name")?;
+                theme = theme::theme(value).ok_or(format!("Unknown theme {value}"))?;
+            }
+            "--background" | "--grid-color" => {
+                let value = iter.next().ok_or(format!("{arg} expects a color r,g,b"))?;
+                let color = parse_color(value).ok_or(format!("Invalid color {value}"))?;
+                if arg == "--background" {
+                    background = Some(color);
+                } else {
+                    grid_color = Some(color);
+                }
+            }
+            "--no-grid" => no_grid = true,
+            "--colorblind" => colorblind = true,
+            "--pulse" => pulse = true,
+            "--wireframe" => wireframe = true,
+            "--emissive" => {
+                let value = iter
+                    .next()
+                    .ok_or("--emissive expects a share from 0 to 1")?;
+                match value.parse::<f32>() {
+                    Ok(e) if (0.0..=1.0).contains(&e) => finish.emissive = e,
+                    _ => return Err(format!("Invalid emissive share {value} (0 to 1)")),
+                }
+            }
+            "--sphere-opacity" => {
+                let value = iter.next().ok_or("--sphere-opacity expects 0 to 1")?;
+                match value.parse::<f32>() {
+                    Ok(o) if o > 0.0 && o <= 1.0 => finish.opacity = o,
+                    _ => return Err(format!("Invalid sphere opacity {value} (above 0, up to 1)")),
+                }
+            }
+            "--slice" => slice = true,
+            "--downbeats" => downbeats = true,
+            "--markers" => markers = true,
+            "--anaglyph" => anaglyph = true,
+            "--orbit" => orbit = true,
+            "--orbit-speed" => {
+                let value = iter
+                    .next()
+                    .ok_or("--orbit-speed expects degrees per second")?;
+                match value.parse::<f32>() {
+                    Ok(s) if s.is_finite() && s.abs() <= 360.0 => orbit_speed = s,
+                    _ => return Err(format!("Invalid orbit speed {value} (-360 to 360)")),
+                }
+            }
+            "--orbit-elevation" => {
+                let value = iter.next().ok_or("--orbit-elevation expects degrees")?;
+                match value.parse::<f32>() {
+                    Ok(e) if (-89.0..=89.0).contains(&e) => orbit_elevation = e,
+                    _ => return Err(format!("Invalid orbit elevation {value} (-89 to 89)")),
+                }
+            }
+            "--auto-frame" => auto_frame = true,
+            "--frame-seconds" => {
+                let value = iter.next().ok_or("--frame-seconds expects seconds")?;
+                match value.parse::<f32>() {
+                    Ok(s) if s.is_finite() && s > 0.0 => frame_seconds = s,
+                    _ => return Err(format!("Invalid frame length {value} (seconds above 0)")),
+                }
+            }
+            "--camera-path" => {
+                let value = iter.next().ok_or("--camera-path expects a file")?;
+                camera_path = Some(PathBuf::from(value));
+            }
+            "--midi-out" => {
+                let value = iter.next().ok_or("--midi-out expects a MIDI port")?;
+                midi_out = Some(PathBuf::from(value));
+            }
+            "--sky" => sky = true,
+            "--grid-size" => {
+                let value = iter.next().ok_or("--grid-size expects a cell size")?;
+                match value.parse::<f32>() {
+                    Ok(size) if size > 0.0 => grid_size = size,
+                    _ => return Err(format!("Invalid grid size {value}")),
+                }
+            }
+            "--position-scale" => {
+                let value = iter
+                    .next()
+                    .ok_or("--position-scale expects a scale or auto")?;
+                position_scale = Scale::from_spec(value).ok_or(format!(
+                    "Invalid position scale {value} (a positive number or auto)"
+                ))?;
+            }
+            "--grid-cells" => {
+                let value = iter.next().ok_or("--grid-cells expects a cell count")?;
+                match value.parse::<u32>() {
+                    Ok(cells) if cells > 0 => grid_cells = cells,
+                    _ => return Err(format!("Invalid grid cell count {value}")),
+                }
+            }
+            "--light" => {
+                let value = iter.next().ok_or("--light expects camera or above")?;
+                lighting =
+                    Some(Lighting::from_name(value).ok_or(format!("Unknown light {value}"))?);
+            }
+            "--key-light" | "--fill-light" | "--sphere-light" => {
+                let value = iter.next().ok_or(format!("{arg} expects an intensity"))?;
+                let light = match arg.as_str() {
+                    "--key-light" => 0,
+                    "--fill-light" => 1,
+                    _ => 2,
+                };
+                match value.parse::<f32>() {
+                    Ok(i) if (0.0..=4.0).contains(&i) => intensities[light] = Some(i),
+                    _ => return Err(format!("Invalid light intensity {value} (0 to 4)")),
+                }
+            }
+            "--glyph" => {
+                let value = iter
+                    .next()
+                    .ok_or("--glyph expects sphere, tetrahedron or both")?;
+                glyph = Glyph::from_name(value).ok_or(format!("Unknown glyph {value}"))?;
+            }
+            "--label-spacing" => {
+                let value = iter
+                    .next()
+                    .ok_or("--label-spacing expects a distance in pixels")?;
+                match value.parse::<f32>() {
+                    Ok(px) if px >= 0.0 => label_spacing = px,
+                    _ => return Err(format!("Invalid label spacing {value}")),
+                }
+            }
+            "--reference" => {
+                let value = iter.next().ok_or("--reference expects a file path")?;
+                references.push(PathBuf::from(value));
+            }
+            "--overlay" => {
+                let start = overlay.len();
+                while let Some(file) = iter.next_if(|arg| !arg.starts_with("--")) {
+                    overlay.push(PathBuf::from(file));
+                }
+                if overlay.len() == start {
+                    return Err("--overlay expects one or more MIDI files".into());
+                }
+            }
+            "--paused" => paused = true,
+            "--export-path" => {
+                let value = iter.next().ok_or("--export-path expects a file path")?;
+                export_path = Some(PathBuf::from(value));
+            }
+            "--export-mesh" => {
+                let value = iter.next().ok_or("--export-mesh expects a file path")?;
+                export_mesh = Some(PathBuf::from(value));
+            }
+            "--mesh-radius" => {
+                let value = iter.next().ok_or("--mesh-radius expects a radius")?;
+                match value.parse::<f32>() {
+                    Ok(r) if r >= 0.0 => mesh_radius = r,
+                    _ => return Err(format!("Invalid mesh radius {value}")),
+                }
+            }
+            "--basis" => {
+                let value = iter.next().ok_or("--basis expects a basis name")?;
+                matrix = transformation::basis(value).ok_or(format!("Unknown basis {value}"))?;
+            }
+            "--matrix" => {
+                let value = iter.next().ok_or("--matrix expects rows or a file path")?;
+                // Either a file holding the matrix or the rows inline
+                let text = if Path::new(value).is_file() {
+                    fs::read_to_string(value).map_err(|e| format!("{value}: {e}"))?
+                } else {
+                    value.clone()
+                };
+                matrix = transformation::parse_matrix(&text)?;
+                transformation::diagnose(matrix)?;
+            }
+            "--rotate" => {
+                let value = iter.next().ok_or("--rotate expects a speed in rad/s")?;
+                rotation_speed = value
+                    .parse()
+                    .map_err(|_| format!("Invalid rotation speed {value}"))?;
+            }
+            "--smooth" => {
+                let value = iter
+                    .next()
+                    .ok_or("--smooth expects average:<n> or exp:<alpha>")?;
+                smoothing =
+                    Some(Smoothing::from_spec(value).ok_or(format!("Invalid smoothing {value}"))?);
+            }
+            "--weights" => {
+                let value = iter.next().ok_or("--weights expects four numbers")?;
+                weights = parse_weights(value).ok_or(format!("Invalid weights {value}"))?;
+            }
+            "--trail-metric" => {
+                let value = iter.next().ok_or("--trail-metric expects l1, l2 or linf")?;
+                trail_metric =
+                    Some(Metric::from_name(value).ok_or(format!("Unknown metric {value}"))?);
+            }
+            "--easing" => {
+                let value = iter.next().ok_or("--easing expects an easing name")?;
+                easing = Easing::from_name(value).ok_or(format!("Unknown easing {value}"))?;
+            }
+            "--trail" => {
+                let value = iter
+                    .next()
+                    .ok_or("--trail expects last:<n>, all, decay:<seconds> or piece")?;
+                trail = Trail::from_spec(value).ok_or(format!("Invalid trail mode {value}"))?;
+            }
+            "--speed" => {
+                let value = iter.next().ok_or("--speed expects a multiplier")?;
+                match value.parse::<f32>() {
+                    Ok(s) if (0.1..=8.0).contains(&s) => speed = s,
+                    _ => return Err(format!("Invalid speed {value} (0.1 to 8)")),
+                }
+            }
+            "--render-video" => {
+                let value = iter.next().ok_or("--render-video expects a file path")?;
+                render_video = Some(PathBuf::from(value));
+            }
+            "--width" | "--height" => {
+                let value = iter
+                    .next()
+                    .ok_or(format!("{arg} expects a size in pixels"))?;
+                let pixels = match value.parse::<u32>() {
+                    Ok(n) if (64..=16384).contains(&n) => n,
+                    _ => return Err(format!("Invalid window size {value} (64 to 16384)")),
+                };
+                sized = true;
+                if arg == "--width" {
+                    window_size.0 = pixels;
+                } else {
+                    window_size.1 = pixels;
+                }
+            }
+            "--no-vsync" => vsync = false,
+            "--msaa" => {
+                let value = iter.next().ok_or("--msaa expects a sample count")?;
+                match value.parse::<u32>() {
+                    Ok(n) if [0, 2, 4, 8, 16].contains(&n) => msaa = n,
+                    _ => return Err(format!("Invalid sample count {value} (0, 2, 4, 8 or 16)")),
+                }
+            }
+            "--smooth-lines" => smooth_lines = true,
+            "--fps" => {
+                let value = iter.next().ok_or("--fps expects a frame rate")?;
+                match value.parse::<u32>() {
+                    Ok(n) if (1..=240).contains(&n) => fps = n,
+                    _ => return Err(format!("Invalid frame rate {value} (1 to 240)")),
+                }
+            }
+            "--export-gif" => {
+                let value = iter.next().ok_or("--export-gif expects a file path")?;
+                export_gif = Some(PathBuf::from(value));
+            }
+            "--export-frames" => {
+                let value = iter.next().ok_or("--export-frames expects a directory")?;
+                frame_dir = Some(PathBuf::from(value));
+            }
+            "--poster" => {
+                let value = iter.next().ok_or("--poster expects a file path")?;
+                poster = Some(PathBuf::from(value));
+            }
+            "--gif-size" => {
+                let value = iter.next().ok_or("--gif-size expects <width>x<height>")?;
+                match parse_pair::<u32>(value, 'x') {
+                    Some((w, h)) if w > 0 && h > 0 => gif_size = (w, h),
+                    _ => return Err(format!("Invalid GIF size {value}")),
+                }
+            }
+            "--bars" => {
+                let value = iter.next().ok_or("--bars expects <first>-<last>")?;
+                match parse_pair::<usize>(value, '-') {
+                    Some((first, last)) if first >= 1 && first <= last => {
+                        bars = Some((first, last));
+                    }
+                    _ => return Err(format!("Invalid bar range {value}")),
+                }
+            }
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {flag}")),
+            _ => {
+                if path.is_some() {
+                    return Err(format!("Unexpected argument {arg}"));
+                }
+                path = Some(PathBuf::from(arg));
+            }
+        }
+    }
+
+    // Individual colors win over the theme wherever they were given
+    theme.background = background.unwrap_or(theme.background);
+    theme.grid = if no_grid {
+        None
+    } else {
+        grid_color.or(theme.grid)
+    };
+    theme.lighting = lighting.unwrap_or(theme.lighting);
+    let [key_light, fill_light, sphere_light] = intensities;
+    theme.intensity.key = key_light.unwrap_or(theme.intensity.key);
+    theme.intensity.fill = fill_light.unwrap_or(theme.intensity.fill);
+    theme.intensity.sphere = sphere_light.unwrap_or(theme.intensity.sphere);
+
+    // Colors told apart by brightness as well as hue, so they survive
+    // red-green color blindness
+    if colorblind {
+        palette = rgba::palette("cividis").expect("cividis is built in");
+        voice_colors = rgba::voice_palette("colorblind").expect("colorblind is built in");
+    }
+
+    // Nothing would ever be seen or saved from an invisible window
+    let outputs = [&render_video, &export_gif, &frame_dir, &poster];
+    let recording = outputs.iter().any(|output| output.is_some());
+    if hidden && !recording {
+        return Err("--hidden only makes sense when recording".into());
+    }
+    if outputs.iter().filter(|output| output.is_some()).count() > 1 {
+        return Err(
+            "Choose one of --render-video, --export-gif, --export-frames and --poster".into(),
+        );
+    }
+    if bars.is_some() && (!recording || poster.is_some()) {
+        return Err(
+            "--bars selects what to record, use it with --render-video, --export-gif \
+             or --export-frames"
+                .into(),
+        );
+    }
+
+    if poster.is_some() && !sized {
+        window_size = POSTER_SIZE;
+    }
+
+    // Only overlays given: load the first as the piece, holding it still so
+    // every path is compared as a whole
+    if path.is_none() && file_path.is_none() && !overlay.is_empty() {
+        path = Some(overlay.remove(0));
+        paused = true;
+    }
+
+    Ok(Config {
+        path: path.or(file_path).ok_or("Missing MIDI file path")?,
+        cues,
+        sequence,
+        fill,
+        analysis,
+        keyframes,
+        isocontours,
+        pitch_class,
+        mapping,
+        absolute,
+        references,
+        overlay,
+        paused,
+        export_path,
+        matrix,
+        energy,
+        projection,
+        rotation_speed,
+        smoothing,
+        weights,
+        trail_metric,
+        trail,
+        per_beat,
+        transposition_invariant,
+        orientation,
+        speed,
+        easing,
+        labels,
+        label_spacing,
+        voices,
+        particles,
+        bloom,
+        axes,
+        shadow,
+        ghost,
+        tube,
+        piano_roll,
+        stats,
+        looping,
+        keep_trail,
+        step,
+        panel,
+        glyph,
+        voice_colors,
+        palette,
+        coloring,
+        pulse,
+        finish,
+        wireframe,
+        slice,
+        downbeats,
+        markers,
+        camera_path,
+        midi_out,
+        anaglyph,
+        orbit,
+        orbit_speed,
+        orbit_elevation,
+        auto_frame,
+        frame_seconds,
+        render_video,
+        fps,
+        hidden,
+        window_size,
+        vsync,
+        msaa,
+        smooth_lines,
+        export_gif,
+        frame_dir,
+        poster,
+        gif_size,
+        bars,
+        export_mesh,
+        mesh_radius,
+        theme,
+        sky,
+        grid_size,
+        grid_cells,
+        position_scale,
+    })
+}
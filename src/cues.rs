@@ -0,0 +1,191 @@
+use std::fs;
+use std::path::Path;
+
+// Pacing cue for a range of bars (1-based, inclusive)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cue {
+    Speed {
+        first_bar: usize,
+        last_bar: usize,
+        factor: f32,
+    },
+    Freeze {
+        first_bar: usize,
+        last_bar: usize,
+        seconds: f32,
+    },
+}
+
+impl Cue {
+    // First and last bar of the range the cue covers
+    fn bars(&self) -> (usize, usize) {
+        match *self {
+            Cue::Speed {
+                first_bar,
+                last_bar,
+                ..
+            }
+            | Cue::Freeze {
+                first_bar,
+                last_bar,
+                ..
+            } => (first_bar, last_bar),
+        }
+    }
+}
+
+// Per-keyframe playback timing derived from the cues
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timing {
+    pub speed: f32, // Playback speed multiplier for the transition
+    pub hold: f32,  // Seconds to hold after arriving at the keyframe
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            hold: 0.0,
+        }
+    }
+}
+
+// Load a cue file. Each non-empty line has the form:
+//   <bar>[-<bar>] speed <factor>    e.g. "9-16 speed 0.5"
+//   <bar>[-<bar>] freeze <seconds>  e.g. "32 freeze 3"  (holds at the end of the range)
+// Everything after a '#' is a comment.
+pub fn load(path: &Path) -> Result<Vec<Cue>, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)?;
+    let mut cues = Vec::new();
+
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let cue = parse_line(line).map_err(|e| format!("cue line {}: {}", line_no + 1, e))?;
+        cues.push(cue);
+    }
+
+    Ok(cues)
+}
+
+fn parse_line(line: &str) -> Result<Cue, String> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() != 3 {
+        return Err(format!(
+            "expected '<bars> <speed|freeze> <value>', got '{line}'"
+        ));
+    }
+
+    let (first_bar, last_bar) = match fields[0].split_once('-') {
+        Some((a, b)) => (parse_bar(a)?, parse_bar(b)?),
+        None => {
+            let bar = parse_bar(fields[0])?;
+            (bar, bar)
+        }
+    };
+    if last_bar < first_bar {
+        return Err(format!("bar range {} is reversed", fields[0]));
+    }
+
+    let value: f32 = fields[2]
+        .parse()
+        .map_err(|_| format!("invalid number '{}'", fields[2]))?;
+
+    match fields[1] {
+        "speed" if value > 0.0 => Ok(Cue::Speed {
+            first_bar,
+            last_bar,
+            factor: value,
+        }),
+        "speed" => Err("speed factor must be positive".into()),
+        "freeze" if value >= 0.0 => Ok(Cue::Freeze {
+            first_bar,
+            last_bar,
+            seconds: value,
+        }),
+        "freeze" => Err("freeze duration cannot be negative".into()),
+        other => Err(format!("unknown cue kind '{other}'")),
+    }
+}
+
+fn parse_bar(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(bar) if bar >= 1 => Ok(bar),
+        _ => Err(format!("invalid bar number '{s}'")),
+    }
+}
+
+// Resolve cues into a timing entry for each motion, given the bar in
+// which every keyframe starts (motion i leads from keyframe i to i + 1)
+pub fn schedule(cues: &[Cue], bars: &[usize]) -> Vec<Timing> {
+    let motions = bars.len().saturating_sub(1);
+    let mut timings = vec![Timing::default(); motions];
+    let bar_of = |index: usize| bars[index];
+
+    for cue in cues {
+        let matched = match *cue {
+            Cue::Speed {
+                first_bar,
+                last_bar,
+                factor,
+            } => {
+                let mut matched = false;
+                for (i, timing) in timings.iter_mut().enumerate() {
+                    if (first_bar..=last_bar).contains(&bar_of(i)) {
+                        timing.speed *= factor;
+                        matched = true;
+                    }
+                }
+                matched
+            }
+            Cue::Freeze {
+                first_bar,
+                last_bar,
+                seconds,
+            } => {
+                // Hold once the last keyframe inside the range is reached
+                let last = (1..bars.len())
+                    .rev()
+                    .find(|&i| bar_of(i) <= last_bar)
+                    .filter(|&i| bar_of(i) >= first_bar);
+                if let Some(last) = last {
+                    timings[last - 1].hold += seconds;
+                }
+                last.is_some()
+            }
+        };
+        if !matched {
+            let range = match cue.bars() {
+                (first_bar, last_bar) if first_bar == last_bar => format!("bar {first_bar}"),
+                (first_bar, last_bar) => format!("bars {first_bar}-{last_bar}"),
+            };
+            eprintln!("[-.-] No keyframe in {range}, ignoring that cue");
+        }
+    }
+
+    timings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freeze_past_the_end_holds_nothing() {
+        let bars: Vec<usize> = (1..=20).collect();
+        let freeze = |first_bar, last_bar| Cue::Freeze {
+            first_bar,
+            last_bar,
+            seconds: 3.0,
+        };
+        let timings = schedule(&[freeze(50, 60)], &bars);
+        assert!(timings.iter().all(|timing| timing.hold == 0.0));
+
+        // Keyframe 9 (bar 10) is the last one inside bars 8-10
+        let timings = schedule(&[freeze(8, 10)], &bars);
+        assert_eq!(timings[8].hold, 3.0);
+        assert_eq!(timings.iter().filter(|timing| timing.hold > 0.0).count(), 1);
+    }
+}
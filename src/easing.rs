@@ -0,0 +1,45 @@
+// Shapes of the move between two keyframes, applied to the linear
+// transition progress
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,      // Constant speed, arrives abruptly
+    Smoothstep,  // Gentle start and landing
+    Cubic,       // Stronger ease-in-out, lingers near each chord
+    Exponential, // Leaves quickly and settles slowly into the next chord
+}
+
+impl Easing {
+    pub fn from_name(name: &str) -> Option<Easing> {
+        match name {
+            "linear" => Some(Easing::Linear),
+            "smoothstep" => Some(Easing::Smoothstep),
+            "cubic" => Some(Easing::Cubic),
+            "exponential" => Some(Easing::Exponential),
+            _ => None,
+        }
+    }
+
+    // Map progress in 0..1 to eased progress in 0..1
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::Smoothstep => t * t * (3.0 - 2.0 * t),
+            Easing::Cubic => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::Exponential => {
+                if t >= 1.0 {
+                    1.0
+                } else {
+                    1.0 - 2f32.powf(-10.0 * t)
+                }
+            }
+        }
+    }
+}
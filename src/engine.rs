@@ -1,69 +1,423 @@
-use crate::rgba;
-use kiss3d::camera::ArcBall;
-use kiss3d::event::{Action, Key, WindowEvent};
-use kiss3d::light::Light;
-use kiss3d::nalgebra::{Point3, Translation3};
+use crate::analysis::{self, Cadence, CadenceKind, Quality, Span};
+use crate::axes;
+use crate::bloom::Bloom;
+use crate::camera::{self, CameraPath, Framing, Frustum, Glide, Orbit, View};
+use crate::capture::{self, Recorder};
+use crate::config::Config;
+use crate::cues::Timing;
+use crate::easing::Easing;
+use crate::glyph;
+use crate::hud::{self, FrameTimer, Hud};
+use crate::isocontour::{self, Isocontours};
+use crate::mapping;
+use crate::panel::{Control, Panel, PanelEvent};
+use crate::picking::{Picker, SceneObject};
+use crate::projection::{self, Projection, Scale};
+use crate::reference::{OverlayPath, ReferencePath};
+use crate::rgba::{Coloring, Palette, Rgb};
+use crate::roll;
+use crate::shading::{self, Shading};
+use crate::sky;
+use crate::smoothing::{self, Smoothing};
+use crate::sound::MidiOut;
+use crate::stereo::{Anaglyph, Eye};
+use crate::trail::Trail;
+use crate::transformation::{self, Metric, MotionType, MotionVec};
+use crate::tube::Tube;
+use kiss3d::camera::{ArcBall, Camera, FirstPerson};
+use kiss3d::context::Context;
+use kiss3d::event::{Action, Key, Modifiers, MouseButton, WindowEvent};
+use kiss3d::nalgebra::{
+    Point2, Point3, Quaternion, Translation3, UnitQuaternion, Vector2, Vector3,
+};
 use kiss3d::scene::SceneNode;
-use kiss3d::window::Window;
+use kiss3d::text::Font;
+use kiss3d::window::{CanvasSetup, NumSamples, Window};
+use std::collections::VecDeque;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 // Constants for animation and visualization
-const POSITION_SCALE: f32 = 1000.0;
+const LINE_SMOOTH: u32 = 0x0B20; // GL_LINE_SMOOTH, which kiss3d's Context does not name
+const FIT_SHARE: f32 = 0.8; // Share of the grid's width an auto-scaled path spans
 const COLOR_SCALE: f32 = 0.03; // More extreme color changes
-const MOTION_SPEED: f32 = 0.125; // 125ms per keyframe (1/16th note at 120 BPM)
-const GRID_SIZE: f32 = 200.0;
-const GRID_CELLS: i32 = 10;
+const GRID_MAX_CELLS: u32 = 100; // Widest grid the extent keys reach
+const GRID_SUBDIVISIONS: i32 = 4; // Sub-grid lines per cell
+const BRACKET_OFFSET: f32 = 40.0; // Height of sequence brackets above the path
+const CADENCE_RADIUS: f32 = 16.0; // Ring around a cadence's arrival
+const CADENCE_SIDES: usize = 32;
+const CADENCE_FLASH: f32 = 0.6; // Seconds the ring flares after the arrival is reached
+const CADENCE_FLARE: f32 = 2.0; // Extra ring radius at the start of the flare, in radii
+const SPHERE_RADIUS: f32 = 30.0;
+const PULSE_GAIN: f32 = 0.8; // Extra sphere scale at the middle of the largest shift (--pulse)
+const TRAIL_VALUE: f32 = 0.7; // Brightness of the newest trail segment
+const TRAIL_SATURATION: f32 = 0.6; // Trail colors are softer than the sphere's
+const TRAIL_CHUNK: usize = 64; // Keyframes per culled piece of the line trail
+const LOD_DISTANCE: f32 = 4000.0; // Distance past which trail segments start joining keyframes
+const LOD_MAX_STRIDE: usize = 16; // Most keyframes one distant segment spans
+const POSTER_FRAME: usize = 3; // Frame saved as the poster, after labels and tube have settled
+const TRAIL_OLDEST: f32 = 0.2; // Relative brightness of the oldest visible segment
+const UPCOMING_FADE: f32 = 0.35; // Brightness of the path still to come (piece trail)
+const GHOST_FADE: f32 = 0.12; // Brightness of the ghost path outside the trail
+const STEP_TIME: f32 = 0.3; // Seconds per transition in step mode
+const TICK_RATE: f64 = 240.0; // Animation updates per second, whatever the frame rate
+const MAX_TICKS: u64 = 240; // Most updates caught up in one frame before the backlog is dropped
+const SHADOW_LIFT: f32 = 1.0; // Height of the ground shadow, just clear of the grid lines
+const SHADOW_COLOR: (f32, f32, f32) = (0.35, 0.35, 0.45);
+const DOWNBEAT_TICK: f32 = 6.0; // Half height of the marks at bar lines along the trail
+const MARKER_RANGE: (f32, f32) = (2.0, 8.0); // Half edge of the shortest and the longest chord's marker
+const UNKNOWN_QUALITY: (f32, f32, f32) = (0.5, 0.5, 0.5); // Marker color of unrecognised chords
+const SLICE_THICKNESS: f32 = 0.05; // Half thickness of the 4D slab, as a share of the w extent
+const SLICE_STEP: f32 = 0.02; // Hyperplane move per key press, as a share of the w extent
+const SLICE_MARK: f32 = 8.0; // Arm length of the star at each crossing
+const PARTICLES_PER_SEMITONE: f32 = 6.0; // Burst size per semitone of voice leading
+const MAX_BURST: usize = 120;
+const PARTICLE_SPEED: f32 = 60.0; // Initial speed of a small burst (units/s)
+const PARTICLE_DRAG: f32 = 2.5; // Fraction of speed lost per second
+const PARTICLE_LIFE: f32 = 0.8; // Seconds a particle stays visible
+const VOICE_RADIUS: f32 = 8.0; // Size of the per-voice spheres
+const VOICE_OFFSET: f32 = 80.0; // How far right of the chord sphere the voices stand
+const VOICE_SPACING: f32 = 25.0; // Horizontal gap between voices
+const VOICE_HEIGHT: f32 = 6.0; // Height per semitone away from middle C
+const KEYFRAME_PICK_RADIUS: f32 = 6.0;
+const CLICK_TOLERANCE: f64 = 4.0; // Max cursor travel (px) for a press to count as a click
+const MOVEMENT_COLORS: [(f32, f32, f32); 4] = [
+    (0.3, 0.7, 1.0),
+    (1.0, 0.6, 0.3),
+    (0.5, 1.0, 0.5),
+    (1.0, 0.4, 0.7),
+];
+const LABEL_SIZE: f32 = 28.0; // Font size of chord-name labels
+const REFERENCE_COLORS: [(f32, f32, f32); 6] = [
+    (0.8, 0.4, 0.9),
+    (0.4, 0.9, 0.5),
+    (0.9, 0.5, 0.3),
+    (0.3, 0.7, 1.0),
+    (1.0, 0.9, 0.3),
+    (0.9, 0.3, 0.5),
+]; // Reference and overlay paths, in load order
+const SPEED_LEVELS: [f32; 9] = [0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 4.0, 8.0]; // Playback multipliers
+const ROTATION_STEP: f32 = 0.05; // Rotation speed change per key press (rad/s)
+const FLY_STEP: f32 = 4.0; // Free-fly distance per frame while an arrow key is held
+const ENERGY_WINDOW: usize = 48; // Motions visible in the energy graph
+const ENERGY_HEIGHT: f32 = 120.0; // Energy graph panel height (px)
+const ENERGY_MARGIN: f32 = 20.0;
+const TEXT_VIEW_SIZE: (usize, usize) = (72, 24); // Columns and rows of the text fallback
+const COLOR_SCALE_RANGE: (f32, f32) = (0.003, 0.3); // Span of the panel's color scale slider
+const TRAIL_RANGE: (f32, f32) = (10.0, 1000.0); // Span of the panel's trail slider (keyframes)
+
+// Everything the engine needs to play a piece
+pub struct Piece {
+    pub chords: Vec<[i32; 4]>,              // Voice pitches of every keyframe
+    pub origin: MotionVec,                  // Location of the first keyframe
+    pub motions: Vec<MotionVec>,            // Voice motion vectors
+    pub durations: Vec<f32>,                // Length of each motion in 16th notes
+    pub seconds: Vec<f32>,                  // Length of each motion in seconds (tempo map)
+    pub bars: Vec<usize>,                   // Bar in which every keyframe starts
+    pub beats: Vec<f32>,                    // Beat within its bar at which every keyframe starts
+    pub timings: Vec<Timing>,               // Cue-driven speed and holds
+    pub spans: Vec<Span>,                   // Labelled keyframe ranges
+    pub cadences: Vec<Cadence>,             // Detected phrase endings
+    pub references: Vec<ReferencePath>,     // Static paths to compare against
+    pub overlays: Vec<OverlayPath>,         // Other pieces' paths, projected with this one
+    pub movements: Vec<Span>,               // Keyframe range of each movement
+    pub motion_types: Vec<[MotionType; 6]>, // Voice-pair motion of each step
+    pub step_sizes: Vec<f32>,               // Voice-leading size of each step (may be empty)
+    pub axes: [String; 4],                  // What each motion component measures, [w, x, y, z]
+}
+
+// Sphere hue for a motion, from its total voice motion
+fn hue(motion: &MotionVec, scale: f32) -> f32 {
+    (motion[0] * scale).abs() % 1.0
+}
+
+// Where every keyframe lands in 4D, optionally smoothed
+fn keyframe_points(
+    origin: MotionVec,
+    motions: &[MotionVec],
+    smoothing: Option<Smoothing>,
+) -> Vec<[f32; 4]> {
+    let steps = match smoothing {
+        Some(smoothing) => smoothing::apply(motions, smoothing),
+        None => motions.to_vec(),
+    };
+    let mut points = vec![origin];
+    for motion in &steps {
+        let last = points[points.len() - 1];
+        points.push([
+            last[0] + motion[0],
+            last[1] + motion[1],
+            last[2] + motion[2],
+            last[3] + motion[3],
+        ]);
+    }
+    points
+}
 
 // Animation state
 struct AnimationState {
-    motions: Vec<[i32; 4]>,             // Voice motion vectors
-    current_position: Point3<f32>,      // Current position
-    target_position: Point3<f32>,       // Target position
-    current_index: usize,               // Current keyframe index
-    transition_progress: f32,           // Progress through current transition (0.0-1.0)
-    current_hue: f32,                   // Current color hue
-    target_hue: f32,                    // Target color hue
-    position_history: Vec<Point3<f32>>, // Trail of past positions
-    timer: f32,                         // Timer for animation
+    motions: Vec<MotionVec>,              // Voice motion vectors
+    seconds: Vec<f32>,                    // Length of each motion in seconds
+    timings: Vec<Timing>,                 // Per-keyframe speed and hold
+    points: Vec<[f32; 4]>,                // 4D location of every keyframe
+    overlays: Vec<Vec<[f32; 4]>>,         // 4D keyframes of every overlaid piece
+    projection: Projection,               // How points are brought into 3D
+    rotation: f32,                        // Current 4D rotation angle (radians)
+    scale: f32,                           // Scene units per semitone of motion
+    path: Vec<Point3<f32>>,               // Position of every keyframe
+    overlay_paths: Vec<Vec<Point3<f32>>>, // Position of every overlaid keyframe
+    bounds: Vec<(Point3<f32>, f32)>,      // Bounding sphere of every TRAIL_CHUNK steps of the path
+    current_position: Point3<f32>,        // Current position
+    target_position: Point3<f32>,         // Target position
+    current_index: usize,                 // Current keyframe index
+    transition_progress: f32,             // Progress through current transition (0.0-1.0)
+    easing: Easing,                       // Shape applied to the progress when drawing
+    current_hue: f32,                     // Current color hue
+    target_hue: f32,                      // Target color hue
+    color_scale: f32,                     // Hue change per semitone of total motion
+    coloring: Coloring,                   // What the hue encodes
+    quality_hues: Vec<f32>,               // Hue of the quality of every keyframe's chord
+    largest_shift: f32,                   // Largest total motion of any step
+    starts: Vec<f32>,                     // Piece time at which each keyframe is reached
+    hold_remaining: f32,                  // Seconds left to hold the current keyframe
+    timer: f32,                           // Timer for animation
 }
 
 impl AnimationState {
     // Create a new animation state
-    fn new(motions: Vec<[i32; 4]>) -> Self {
-        let current_position = Point3::new(0.0, 0.0, 0.0);
-
-        // Calculate initial target position and hue
+    fn new(
+        origin: MotionVec,
+        motions: Vec<MotionVec>,
+        seconds: Vec<f32>,
+        timings: Vec<Timing>,
+        projection: Projection,
+        smoothing: Option<Smoothing>,
+        easing: Easing,
+    ) -> Self {
+        // Calculate initial hue
         let first_motion = if !motions.is_empty() {
             motions[0]
         } else {
-            [0, 0, 0, 0]
+            [0.0; 4]
         };
-        let target_position = Point3::new(
-            first_motion[1] as f32 * POSITION_SCALE / 100.0,
-            first_motion[2] as f32 * POSITION_SCALE / 100.0,
-            first_motion[3] as f32 * POSITION_SCALE / 100.0,
-        );
+        let initial_hue = (first_motion[0] * COLOR_SCALE).abs() % 1.0;
+
+        let points = keyframe_points(origin, &motions, smoothing);
+        let largest_shift = motions.iter().map(|m| m[0].abs()).fold(0.0, f32::max);
 
-        let initial_hue = (first_motion[0] as f32 * COLOR_SCALE).abs() % 1.0;
+        let mut starts = vec![0.0];
+        for length in &seconds {
+            starts.push(starts[starts.len() - 1] + length);
+        }
 
-        Self {
+        let mut state = Self {
             motions,
-            current_position,
-            target_position,
+            seconds,
+            timings,
+            points,
+            overlays: Vec::new(),
+            projection,
+            rotation: 0.0,
+            scale: 10.0,
+            path: Vec::new(),
+            overlay_paths: Vec::new(),
+            bounds: Vec::new(),
+            current_position: Point3::origin(),
+            target_position: Point3::origin(),
             current_index: 0,
             transition_progress: 0.0,
+            easing,
             current_hue: initial_hue,
             target_hue: initial_hue,
-            position_history: Vec::new(),
+            color_scale: COLOR_SCALE,
+            coloring: Coloring::Motion,
+            quality_hues: Vec::new(),
+            largest_shift,
+            starts,
+            hold_remaining: 0.0,
             timer: 0.0,
+        };
+        state.reproject();
+        state
+    }
+
+    // Jump to the start of a motion; positions, trail and colour are
+    // rebuilt from the keyframe path so seeking is deterministic
+    fn seek(&mut self, index: usize) {
+        self.current_index = index.min(self.motions.len() - 1);
+        self.transition_progress = 0.0;
+        self.hold_remaining = 0.0;
+        self.recolor();
+        self.reproject();
+    }
+
+    // Back to the very beginning, as when the piece was opened
+    fn restart(&mut self) {
+        self.seek(0);
+        self.timer = 0.0;
+    }
+
+    // Change how fast the hue cycles, recoloring the current transition
+    fn set_color_scale(&mut self, scale: f32) {
+        self.color_scale = scale;
+        self.recolor();
+    }
+
+    // Switch what the hue encodes; quality hues come from the chord at
+    // every keyframe, a chord that is not recognised keeping the quality
+    // before it
+    fn set_coloring(&mut self, coloring: Coloring, chords: &[[i32; 4]]) {
+        if coloring == Coloring::Quality && self.quality_hues.is_empty() {
+            let detected: Vec<Option<f32>> = chords
+                .iter()
+                .map(|&chord| analysis::detect_chord(chord).map(|name| name.quality.hue()))
+                .collect();
+            let first = detected.iter().flatten().next().copied();
+            let mut last = first.unwrap_or_else(|| Quality::Major.hue());
+            self.quality_hues = detected
+                .iter()
+                .map(|found| {
+                    last = found.unwrap_or(last);
+                    last
+                })
+                .collect();
         }
+        self.coloring = coloring;
+        self.recolor();
+    }
+
+    // Hue on arriving at the end of a motion
+    fn motion_hue(&self, index: usize) -> f32 {
+        match self.coloring {
+            Coloring::Motion => self
+                .motions
+                .get(index)
+                .map_or(0.0, |motion| hue(motion, self.color_scale)),
+            Coloring::Quality => {
+                let last = self.quality_hues.len().saturating_sub(1);
+                self.quality_hues
+                    .get((index + 1).min(last))
+                    .copied()
+                    .unwrap_or(0.0)
+            }
+        }
+    }
+
+    // Hues of the current transition, from the motions around it
+    fn recolor(&mut self) {
+        self.target_hue = self.motion_hue(self.current_index);
+        self.current_hue = match self.current_index.checked_sub(1) {
+            Some(previous) => self.motion_hue(previous),
+            None => self.target_hue,
+        };
+    }
+
+    // Recompute the 3D path from the rotated 4D points, moving the sphere
+    // and trail to match
+    fn reproject(&mut self) {
+        let rotated = projection::rotate(&self.points, self.rotation);
+        let to_scene = |p: &[f32; 3]| Point3::from(p.map(|v| v * self.scale));
+        self.path = projection::project(&rotated, self.projection)
+            .iter()
+            .map(to_scene)
+            .collect();
+        self.overlay_paths = self
+            .overlays
+            .iter()
+            .map(|points| {
+                let points = projection::rotate(points, self.rotation);
+                projection::project_beside(&points, &rotated, self.projection)
+                    .iter()
+                    .map(to_scene)
+                    .collect()
+            })
+            .collect();
+        let steps = self.path.len().saturating_sub(1);
+        self.bounds = (0..steps.div_ceil(TRAIL_CHUNK))
+            .map(|chunk| {
+                let end = ((chunk + 1) * TRAIL_CHUNK).min(steps);
+                let points = &self.path[chunk * TRAIL_CHUNK..=end];
+                let sum = points
+                    .iter()
+                    .fold(Vector3::zeros(), |sum, p| sum + p.coords);
+                let center = Point3::from(sum / points.len() as f32);
+                let radius = points
+                    .iter()
+                    .map(|p| (p - center).norm())
+                    .fold(0.0, f32::max);
+                (center, radius)
+            })
+            .collect();
+
+        let index = self.current_index.min(self.path.len() - 1);
+        self.current_position = self.path[index];
+        self.target_position = self.path[(index + 1).min(self.path.len() - 1)];
+    }
+
+    // Other pieces' 4D keyframes, from now on moved with the path
+    fn set_overlays(&mut self, overlays: Vec<Vec<[f32; 4]>>) {
+        self.overlays = overlays;
+        self.reproject();
+    }
+
+    // Place 4D points lying near the path in the scene, rotated, projected
+    // and scaled as the path is
+    fn place(&self, points: &[[f32; 4]]) -> Vec<Point3<f32>> {
+        let path = projection::rotate(&self.points, self.rotation);
+        let rotated = projection::rotate(points, self.rotation);
+        projection::project_beside(&rotated, &path, self.projection)
+            .iter()
+            .map(|p| Point3::from(p.map(|v| v * self.scale)))
+            .collect()
+    }
+
+    // Spread the path out by `scale`, fitting it to a cube the width of
+    // the grid when asked to; the fit uses the projection in use now
+    fn set_scale(&mut self, scale: Scale, grid_width: f32) {
+        self.scale = match scale {
+            Scale::Fixed(scale) => scale / 100.0,
+            Scale::Fit => {
+                let span = |axis: usize| {
+                    let values = self.path.iter().map(|p| p[axis]);
+                    let high = values.clone().fold(f32::MIN, f32::max);
+                    let low = values.fold(f32::MAX, f32::min);
+                    high - low
+                };
+                let widest = (0..3).map(span).fold(0.0, f32::max) / self.scale;
+                if widest > 1e-6 {
+                    FIT_SHARE * grid_width / widest
+                } else {
+                    self.scale
+                }
+            }
+        };
+        self.reproject();
     }
 
     // Update animation state
     fn update(&mut self, delta_time: f32) -> bool {
         self.timer += delta_time;
 
+        // Stay on a frozen keyframe until its hold time has elapsed
+        if self.hold_remaining > 0.0 {
+            self.hold_remaining -= delta_time;
+            if self.hold_remaining > 0.0 || self.current_index < self.motions.len() {
+                return true;
+            }
+            println!("Animation complete - reached the end of keyframes");
+            return false;
+        }
+
         // Update transition progress
-        self.transition_progress += delta_time / MOTION_SPEED;
+        let speed = self.timing(self.current_index).speed;
+        let length = self
+            .seconds
+            .get(self.current_index)
+            .copied()
+            .unwrap_or(0.125);
+        self.transition_progress += delta_time * speed / length.max(1e-3);
 
         // Check if we need to move to the next keyframe
         if self.transition_progress >= 1.0 {
@@ -71,17 +425,19 @@ impl AnimationState {
             self.transition_progress = 0.0;
             self.current_position = self.target_position;
 
-            // Add to trail history
-            self.position_history.push(self.current_position);
-            if self.position_history.len() > 100 {
-                self.position_history.remove(0);
-            }
+            // Freeze on this keyframe if a cue asks for it
+            self.hold_remaining = self.timing(self.current_index).hold;
 
             // Move to next motion index
             self.current_index += 1;
 
             // Check if we've reached the end
             if self.current_index >= self.motions.len() {
+                // Let a freeze on the final chord play out first
+                if self.hold_remaining > 0.0 {
+                    return true;
+                }
+
                 // We've reached the end, stop the animation
                 println!("Animation complete - reached the end of keyframes");
                 return false;
@@ -90,16 +446,10 @@ impl AnimationState {
             self.current_hue = self.target_hue;
 
             // Calculate next target hue
-            let motion = self.motions[self.current_index];
-            let total_motion = motion[0] as f32 * COLOR_SCALE;
-            self.target_hue = total_motion.abs() % 1.0;
+            self.target_hue = self.motion_hue(self.current_index);
 
             // Calculate next target position
-            self.target_position = Point3::new(
-                self.current_position.x + motion[1] as f32 * POSITION_SCALE / 100.0,
-                self.current_position.y + motion[2] as f32 * POSITION_SCALE / 100.0,
-                self.current_position.z + motion[3] as f32 * POSITION_SCALE / 100.0,
-            );
+            self.target_position = self.path[self.current_index + 1];
 
             /*println!(
                 "Keyframe {}/{}: Position: ({:.2}, {:.2}, {:.2})",
@@ -115,20 +465,103 @@ impl AnimationState {
         true
     }
 
+    // Get timing for a keyframe (defaults when no cue applies)
+    fn timing(&self, index: usize) -> Timing {
+        self.timings.get(index).copied().unwrap_or_default()
+    }
+
+    // Lowest and highest w of the rotated 4D keyframes
+    fn w_extent(&self) -> (f32, f32) {
+        projection::rotate(&self.points, self.rotation)
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(low, high), p| {
+                (low.min(p[0]), high.max(p[0]))
+            })
+    }
+
     // Get interpolated position
     fn interpolated_position(&self) -> Point3<f32> {
+        let t = self.eased();
         Point3::new(
-            self.current_position.x
-                + (self.target_position.x - self.current_position.x) * self.transition_progress,
-            self.current_position.y
-                + (self.target_position.y - self.current_position.y) * self.transition_progress,
-            self.current_position.z
-                + (self.target_position.z - self.current_position.z) * self.transition_progress,
+            self.current_position.x + (self.target_position.x - self.current_position.x) * t,
+            self.current_position.y + (self.target_position.y - self.current_position.y) * t,
+            self.current_position.z + (self.target_position.z - self.current_position.z) * t,
         )
     }
 
+    // Voice pitches between the current chord and the next
+    fn interpolated_voices(&self, chords: &[[i32; 4]]) -> [f32; 4] {
+        let from = chords[self.current_index.min(chords.len() - 1)];
+        let to = chords[(self.current_index + 1).min(chords.len() - 1)];
+        std::array::from_fn(|v| from[v] as f32 + (to[v] - from[v]) as f32 * self.eased())
+    }
+
+    // Orientation driven by the whole 4-vector of a motion read as a
+    // quaternion (w = total, then x, y, z); steps without motion keep the
+    // previous orientation
+    fn motion_orientation(&self, index: usize) -> UnitQuaternion<f32> {
+        self.motions
+            .get(index)
+            .into_iter()
+            .chain(self.motions[..index.min(self.motions.len())].iter().rev())
+            .find(|m| m.iter().any(|&v| v != 0.0))
+            .map_or(UnitQuaternion::identity(), |m| {
+                UnitQuaternion::from_quaternion(Quaternion::new(m[0], m[1], m[2], m[3]))
+            })
+    }
+
+    // Get interpolated orientation
+    fn interpolated_orientation(&self) -> UnitQuaternion<f32> {
+        let from = match self.current_index.checked_sub(1) {
+            Some(previous) => self.motion_orientation(previous),
+            None => UnitQuaternion::identity(),
+        };
+        let to = self.motion_orientation(self.current_index);
+        from.try_slerp(&to, self.eased(), 1e-6).unwrap_or(to)
+    }
+
+    // Hue the sphere has on arriving at a keyframe
+    fn keyframe_hue(&self, index: usize) -> f32 {
+        let last = self.motions.len().saturating_sub(1);
+        self.motion_hue(index.saturating_sub(1).min(last))
+    }
+
+    // Sphere scale with --pulse: swelling over the middle of each step by
+    // how large its total motion is next to the largest in the piece
+    fn pulse(&self) -> f32 {
+        if self.largest_shift == 0.0 {
+            return 1.0;
+        }
+        let index = self.current_index.min(self.motions.len() - 1);
+        let size = self.motions[index][0].abs() / self.largest_shift;
+        1.0 + PULSE_GAIN * size * (std::f32::consts::PI * self.eased()).sin()
+    }
+
+    // Transition progress after easing, for everything drawn in between keyframes
+    fn eased(&self) -> f32 {
+        self.easing.apply(self.transition_progress)
+    }
+
+    // The sphere and the keyframes it reached in the last `seconds` of
+    // piece time
+    fn recent_path(&self, seconds: f32) -> Vec<Point3<f32>> {
+        let index = self.current_index.min(self.path.len() - 1);
+        let since = self.elapsed() - seconds;
+        let first = self.starts[..=index].partition_point(|&start| start < since);
+        let mut recent = self.path[first.min(index)..=index].to_vec();
+        recent.push(self.interpolated_position());
+        recent
+    }
+
+    // Piece time reached, in seconds at the file's tempo
+    fn elapsed(&self) -> f32 {
+        let done = self.starts[self.current_index.min(self.starts.len() - 1)];
+        let current = self.seconds.get(self.current_index).copied().unwrap_or(0.0);
+        done + current * self.transition_progress
+    }
+
     // Get interpolated color
-    fn interpolated_color(&self) -> (f32, f32, f32) {
+    fn interpolated_color(&self, palette: Palette) -> (f32, f32, f32) {
         // Interpolate hue (find shortest path around color wheel)
         let mut hue_diff = self.target_hue - self.current_hue;
         if hue_diff.abs() > 0.5 {
@@ -138,179 +571,2251 @@ impl AnimationState {
                 hue_diff + 1.0
             };
         }
-        let interpolated_hue = (self.current_hue + hue_diff * self.transition_progress).fract();
+        let interpolated_hue = (self.current_hue + hue_diff * self.eased()).fract();
 
-        // Convert HSV to RGB using our rgba module
-        rgba::hsv_to_rgb(interpolated_hue, 1.0, 1.0)
+        palette.at(interpolated_hue)
     }
 }
 
-// Create grid for reference
-fn create_grid(window: &mut Window) -> Vec<SceneNode> {
-    let mut grid_lines = Vec::new();
+// A spark thrown off when a new keyframe starts
+struct Particle {
+    position: Point3<f32>,  // Current location
+    velocity: Vector3<f32>, // Units per second, slowed by drag
+    age: f32,               // Seconds since it was emitted
+    color: Rgb,             // Sphere color at the moment of the burst
+}
 
-    // Create grid lines along X and Z axes
-    for i in -GRID_CELLS..=GRID_CELLS {
-        let pos = i as f32 * GRID_SIZE;
+// Short-lived particle bursts punctuating chord changes
+#[derive(Default)]
+struct Particles {
+    items: Vec<Particle>, // Live particles
+    seed: u32,            // State of the xorshift generator scattering directions
+}
 
-        // Create lines using cylinders
-        // X-axis lines
-        let mut line_x = window.add_cylinder(2.0, GRID_SIZE * GRID_CELLS as f32 * 2.0);
-        line_x.set_color(0.3, 0.3, 0.4);
-        line_x.set_local_translation(Translation3::new(0.0, 0.0, pos));
-        line_x.set_local_rotation(kiss3d::nalgebra::UnitQuaternion::from_axis_angle(
-            &kiss3d::nalgebra::Vector3::z_axis(),
-            std::f32::consts::FRAC_PI_2,
-        ));
-        grid_lines.push(line_x);
-
-        // Z-axis lines
-        let mut line_z = window.add_cylinder(2.0, GRID_SIZE * GRID_CELLS as f32 * 2.0);
-        line_z.set_color(0.3, 0.3, 0.4);
-        line_z.set_local_translation(Translation3::new(pos, 0.0, 0.0));
-        line_z.set_local_rotation(kiss3d::nalgebra::UnitQuaternion::from_axis_angle(
-            &kiss3d::nalgebra::Vector3::x_axis(),
-            std::f32::consts::FRAC_PI_2,
-        ));
-        grid_lines.push(line_z);
+impl Particles {
+    // Next pseudo-random number in -1..1
+    fn random(&mut self) -> f32 {
+        let mut x = if self.seed == 0 {
+            0x9e37_79b9
+        } else {
+            self.seed
+        };
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.seed = x;
+        x as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+
+    // Emit a burst whose size and speed grow with the voice-leading distance
+    fn burst(&mut self, center: Point3<f32>, distance: f32, color: Rgb) {
+        let count = ((distance * PARTICLES_PER_SEMITONE) as usize).min(MAX_BURST);
+        let speed = PARTICLE_SPEED * (1.0 + distance / 12.0);
+        for _ in 0..count {
+            let direction = Vector3::new(self.random(), self.random(), self.random());
+            let Some(direction) = direction.try_normalize(1e-3) else {
+                continue;
+            };
+            let spread = 0.5 + 0.5 * self.random().abs();
+            self.items.push(Particle {
+                position: center,
+                velocity: direction * speed * spread,
+                age: 0.0,
+                color,
+            });
+        }
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        for particle in &mut self.items {
+            particle.position += particle.velocity * delta_time;
+            particle.velocity *= (1.0 - PARTICLE_DRAG * delta_time).max(0.0);
+            particle.age += delta_time;
+        }
+        self.items.retain(|particle| particle.age < PARTICLE_LIFE);
     }
 
-    grid_lines
+    // Particles darken towards the background as they age
+    fn draw(&self, window: &mut Window) {
+        for particle in &self.items {
+            let fade = 1.0 - particle.age / PARTICLE_LIFE;
+            let (r, g, b) = particle.color;
+            window.draw_point(
+                &particle.position,
+                &Point3::new(r * fade, g * fade, b * fade),
+            );
+        }
+    }
 }
 
-// Create trail lines to show path
-fn update_trail(window: &mut Window, state: &AnimationState, trail_nodes: &mut Vec<SceneNode>) {
-    // Remove old trail nodes
-    for mut node in trail_nodes.drain(..) {
-        window.remove_node(&mut node);
+// Reference grid on the y = 0 plane: every cell outlined, each divided
+// by a dimmer sub-grid halfway to the background
+fn draw_grid(window: &mut Window, (r, g, b): Rgb, background: Rgb, cell: f32, cells: u32) {
+    let cells = cells as i32;
+    let extent = cell * cells as f32;
+    let (br, bg, bb) = background;
+    let major = Point3::new(r, g, b);
+    let minor = Point3::new((r + br) / 2.0, (g + bg) / 2.0, (b + bb) / 2.0);
+    let lines = cells * GRID_SUBDIVISIONS;
+    for i in -lines..=lines {
+        let pos = i as f32 * cell / GRID_SUBDIVISIONS as f32;
+        let color = if i % GRID_SUBDIVISIONS == 0 {
+            &major
+        } else {
+            &minor
+        };
+        window.draw_line(
+            &Point3::new(-extent, 0.0, pos),
+            &Point3::new(extent, 0.0, pos),
+            color,
+        );
+        window.draw_line(
+            &Point3::new(pos, 0.0, -extent),
+            &Point3::new(pos, 0.0, extent),
+            color,
+        );
     }
+}
 
-    // Add new trail segments if we have history
-    if state.position_history.len() > 1 {
-        for i in 1..state.position_history.len() {
-            let p1 = state.position_history[i - 1];
-            let p2 = state.position_history[i];
+// Color of every trail segment before fading, worked out once rather than
+// per segment per frame, which adds up over thousands of keyframes; rebuilt
+// when the color scale changes
+struct TrailColors {
+    chroma: Vec<Rgb>, // Full-brightness color of each step
+    value: Vec<f32>,  // Brightness of each step before fading
+}
 
-            // Create thin lines instead of cylinders
-            let mut line = window.add_cylinder(1.0, 1.0); // Just a placeholder that won't be visible
-            line.set_visible(false); // Don't show the cylinders
+impl TrailColors {
+    // Each segment keeps the hue the sphere had when it got there, and
+    // larger voice-leading steps draw brighter (unchanged when unused)
+    fn new(state: &AnimationState, step_sizes: &[f32], palette: Palette) -> TrailColors {
+        let largest = step_sizes.iter().copied().fold(0.0, f32::max);
+        let steps = 0..state.path.len();
+        TrailColors {
+            chroma: steps
+                .clone()
+                .map(|step| palette.soft(state.keyframe_hue(step + 1), TRAIL_SATURATION))
+                .collect(),
+            value: steps
+                .map(|step| match step_sizes.get(step) {
+                    Some(size) if largest > 0.0 => TRAIL_VALUE * (0.4 + 1.2 * size / largest),
+                    _ => TRAIL_VALUE,
+                })
+                .collect(),
+        }
+    }
 
-            // Get points along the line
-            let num_segments = 8; // Number of points to create along the line
-            for j in 0..num_segments {
-                let t = j as f32 / (num_segments - 1) as f32;
-                let pos = Point3::new(
-                    p1.x + (p2.x - p1.x) * t,
-                    p1.y + (p2.y - p1.y) * t,
-                    p1.z + (p2.z - p1.z) * t,
-                );
+    fn at(&self, step: usize, fade: f32) -> Point3<f32> {
+        let step = step.min(self.chroma.len() - 1);
+        let (r, g, b) = self.chroma[step];
+        let value = (self.value[step] * fade).min(1.0);
+        Point3::new(r * value, g * value, b * value)
+    }
+}
+
+// Where and how the sphere is drawn at one animation tick; frames fall
+// between ticks and blend the last two
+#[derive(Clone, Copy)]
+struct Pose {
+    index: usize,          // Keyframe the sphere is leaving
+    position: Point3<f32>, // Sphere position
+    color: Rgb,            // Sphere color
+    voices: [f32; 4],      // Voice pitches, fractional mid-motion
+}
+
+impl Pose {
+    fn of(state: &AnimationState, palette: Palette, chords: &[[i32; 4]]) -> Pose {
+        Pose {
+            index: state.current_index,
+            position: state.interpolated_position(),
+            color: state.interpolated_color(palette),
+            voices: state.interpolated_voices(chords),
+        }
+    }
+
+    // `alpha` of the way from this pose to the next; a jump of more than
+    // one keyframe (seeking, looping) shows the new pose outright
+    fn blend(self, next: Pose, alpha: f32) -> Pose {
+        if next.index.abs_diff(self.index) > 1 {
+            return next;
+        }
+        let mix = |a: f32, b: f32| a + (b - a) * alpha;
+        let (r, g, b) = self.color;
+        let (nr, ng, nb) = next.color;
+        Pose {
+            index: next.index,
+            position: self.position + (next.position - self.position) * alpha,
+            color: (mix(r, nr), mix(g, ng), mix(b, nb)),
+            voices: std::array::from_fn(|v| mix(self.voices[v], next.voices[v])),
+        }
+    }
+}
 
-                // Create a small sphere at each point
-                let mut point = window.add_sphere(1.5);
-                point.set_color(0.4, 0.5, 0.6);
-                point.set_local_translation(Translation3::new(pos.x, pos.y, pos.z));
-                trail_nodes.push(point);
+// Lines between keyframes from..=to colored by `color` of each step, none
+// where it gives None; pieces of the path out of view are skipped, and
+// with `lod` distant ones join several keyframes per line so long pieces
+// stay light
+fn draw_path(
+    window: &mut Window,
+    state: &AnimationState,
+    frustum: &Frustum,
+    (from, to): (usize, usize),
+    lod: bool,
+    color: impl Fn(usize) -> Option<Point3<f32>>,
+) {
+    let mut start = from;
+    while start < to {
+        let chunk = start / TRAIL_CHUNK;
+        let end = ((chunk + 1) * TRAIL_CHUNK).min(to);
+        let (center, radius) = state.bounds[chunk];
+        if frustum.sees(&center, radius) {
+            let distance = (center - frustum.eye).norm() - radius;
+            let mut stride = 1;
+            while lod && stride < LOD_MAX_STRIDE && distance > LOD_DISTANCE * stride as f32 {
+                stride *= 2;
             }
+            let mut step = start;
+            while step < end {
+                let next = (step + stride).min(end);
+                if let Some(color) = color(step) {
+                    window.draw_line(&state.path[step], &state.path[next], &color);
+                }
+                step = next;
+            }
+        }
+        start = end;
+    }
+}
+
+// Draw the trail as lines through the reached keyframes up to the sphere;
+// immediate-mode lines leave no scene nodes to create or remove. With
+// `ghost`, the rest of the path is drawn dim all along, so the
+// trail lights the path up as it is reached
+fn draw_trail(
+    window: &mut Window,
+    camera: &dyn Camera,
+    state: &AnimationState,
+    colors: &TrailColors,
+    trail: Trail,
+    ghost: bool,
+    glow: f32,
+) {
+    let frustum = Frustum::of(camera);
+    let index = state.current_index.min(state.path.len() - 1);
+    let first = trail.first_keyframe(index);
 
-            trail_nodes.push(line); // Still need to add the invisible line to clean it up later
+    // Older segments dim along the visible trail, and decaying trails
+    // also fade out by the time since each segment was completed
+    let now = state.elapsed();
+    let fade = |step: usize| {
+        let span = (index + 1 - first) as f32;
+        let age = (index - step) as f32 / span;
+        let gradient = 1.0 - (1.0 - TRAIL_OLDEST) * age;
+        match trail {
+            Trail::Decay(seconds) => {
+                let since = now - state.starts.get(step + 1).copied().unwrap_or(now);
+                gradient * (1.0 - since / seconds)
+            }
+            _ => gradient,
         }
+    };
 
-        // Add segment from last history point to current position
-        if let Some(last) = state.position_history.last() {
-            let current_pos = state.interpolated_position();
+    // The sphere's own light brightens the path around it
+    let sphere = state.interpolated_position();
+    let lit = |step: usize, fade: f32| {
+        let distance = (state.path[step] - sphere).norm();
+        colors.at(step, fade + glow * shading::sphere_light_falloff(distance))
+    };
 
-            // Create thin line from dotted points
-            let mut line = window.add_cylinder(1.0, 1.0); // Just a placeholder
-            line.set_visible(false); // Don't show the cylinder
+    if ghost && first > 1 {
+        let before = first.min(index);
+        draw_path(window, state, &frustum, (1, before), true, |step| {
+            Some(lit(step, GHOST_FADE))
+        });
+    }
+    if first <= index {
+        draw_path(window, state, &frustum, (first, index), true, |step| {
+            let fade = fade(step);
+            if fade > GHOST_FADE || (!ghost && fade > 0.0) {
+                Some(lit(step, fade))
+            } else {
+                ghost.then(|| lit(step, GHOST_FADE))
+            }
+        });
+        // Segment from the last reached keyframe to the sphere
+        window.draw_line(
+            &state.path[index],
+            &state.interpolated_position(),
+            &colors.at(state.current_index, 1.0),
+        );
+    }
 
-            // Get points along the line
-            let num_segments = 8; // Number of points to create along the line
-            for j in 0..num_segments {
-                let t = j as f32 / (num_segments - 1) as f32;
-                let pos = Point3::new(
-                    last.x + (current_pos.x - last.x) * t,
-                    last.y + (current_pos.y - last.y) * t,
-                    last.z + (current_pos.z - last.z) * t,
-                );
+    // The rest of the piece, still to come
+    let upcoming = match trail {
+        Trail::Piece => Some(UPCOMING_FADE),
+        _ => ghost.then_some(GHOST_FADE),
+    };
+    if let Some(fade) = upcoming
+        && index + 1 < state.path.len()
+    {
+        window.draw_line(
+            &state.interpolated_position(),
+            &state.path[index + 1],
+            &colors.at(state.current_index, fade),
+        );
+        let last = state.path.len() - 1;
+        draw_path(window, state, &frustum, (index + 1, last), true, |step| {
+            Some(lit(step, fade))
+        });
+    }
+}
+
+// Short upright ticks on the visible trail wherever a new bar begins, so
+// the path can be read against the meter
+fn draw_downbeats(
+    window: &mut Window,
+    state: &AnimationState,
+    bars: &[usize],
+    trail: Trail,
+    color: Rgb,
+) {
+    let (r, g, b) = color;
+    let color = Point3::new(r, g, b);
+    let index = state.current_index.min(state.path.len() - 1);
+    let last = if trail == Trail::Piece {
+        state.path.len() - 1
+    } else {
+        index
+    };
+    let tick = Vector3::new(0.0, DOWNBEAT_TICK, 0.0);
+    for k in trail.first_keyframe(index).max(1)..=last.min(bars.len().saturating_sub(1)) {
+        if bars[k] > bars[k - 1] {
+            window.draw_line(&(state.path[k] - tick), &(state.path[k] + tick), &color);
+        }
+    }
+}
+
+// Cross-section of the rotated 4D path by the hyperplane w = level, in
+// the drop-w coordinates it shares with the sphere: the path inside a thin
+// slab around the hyperplane, fading away from it, and a star wherever it
+// passes through
+fn draw_slice(window: &mut Window, state: &AnimationState, colors: &TrailColors, level: f32) {
+    let (low, high) = state.w_extent();
+    let half = ((high - low) * SLICE_THICKNESS).max(1e-3);
+    let points = projection::rotate(&state.points, state.rotation);
+    let scene = |p: [f32; 3]| Point3::from(p.map(|v| v * state.scale));
+    for piece in projection::slab(&points, level, half) {
+        let color = colors.at(piece.segment, 1.0 - piece.distance);
+        window.draw_line(&scene(piece.start), &scene(piece.end), &color);
+        if let Some(crossing) = piece.crossing {
+            let center = scene(crossing);
+            let color = colors.at(piece.segment, 1.5);
+            for i in 0..3 {
+                let arm = Vector3::ith(i, SLICE_MARK);
+                window.draw_line(&(center - arm), &(center + arm), &color);
+            }
+        }
+    }
+}
+
+// The whole path as the previous pass left it, dimmed like the part of a
+// piece trail still to come
+fn draw_previous_pass(window: &mut Window, state: &AnimationState, colors: &TrailColors) {
+    for (i, pair) in state.path.windows(2).enumerate() {
+        window.draw_line(&pair[0], &pair[1], &colors.at(i, UPCOMING_FADE));
+    }
+}
+
+// A small wireframe cube left at every keyframe reached, sized by how long
+// its chord lasts and colored by its quality, so the whole piece stays
+// readable as a score in space once the trail has faded
+struct Markers {
+    sizes: Vec<f32>,          // Half edge of each keyframe's cube
+    colors: Vec<Point3<f32>>, // Quality color of each keyframe's chord
+}
+
+impl Markers {
+    fn new(state: &AnimationState, chords: &[[i32; 4]], palette: Palette) -> Markers {
+        let longest = state.seconds.iter().copied().fold(0.0, f32::max);
+        let (low, high) = MARKER_RANGE;
+        let sizes = (0..state.path.len())
+            .map(|k| match state.seconds.get(k) {
+                Some(length) if longest > 0.0 => low + (high - low) * (length / longest).sqrt(),
+                _ => low,
+            })
+            .collect();
+        let colors = chords
+            .iter()
+            .map(|&chord| {
+                let (r, g, b) = analysis::detect_chord(chord)
+                    .map_or(UNKNOWN_QUALITY, |name| palette.at(name.quality.hue()));
+                Point3::new(r, g, b)
+            })
+            .collect();
+        Markers { sizes, colors }
+    }
 
-                // Create a small sphere at each point
-                let mut point = window.add_sphere(1.5);
-                point.set_color(0.4, 0.5, 0.6);
-                point.set_local_translation(Translation3::new(pos.x, pos.y, pos.z));
-                trail_nodes.push(point);
+    fn draw(&self, window: &mut Window, state: &AnimationState) {
+        let reached = state.current_index.min(state.path.len() - 1);
+        for (k, center) in state.path[..=reached].iter().enumerate() {
+            let size = self.sizes[k];
+            let color = self
+                .colors
+                .get(k)
+                .copied()
+                .unwrap_or(Point3::new(1.0, 1.0, 1.0));
+            let corner = |i: usize| {
+                let sign = |bit: usize| if i & bit == 0 { -size } else { size };
+                center + Vector3::new(sign(1), sign(2), sign(4))
+            };
+            // Edges join corners that differ in one coordinate
+            for i in 0..8 {
+                for bit in [1, 2, 4] {
+                    if i & bit == 0 {
+                        window.draw_line(&corner(i), &corner(i | bit), &color);
+                    }
+                }
             }
+        }
+    }
+}
+
+// Flatten the trail onto the grid plane, with a drop line from the sphere
+// to its shadow, so height reads apart from the path's xz shape
+fn draw_shadow(window: &mut Window, state: &AnimationState, trail: Trail) {
+    let (r, g, b) = SHADOW_COLOR;
+    let color = Point3::new(r, g, b);
+    let flat = |p: &Point3<f32>| Point3::new(p.x, SHADOW_LIFT, p.z);
+
+    let index = state.current_index.min(state.path.len() - 1);
+    let first = trail.first_keyframe(index).min(index);
+    let sphere = state.interpolated_position();
+    let reached = state.path[first..=index]
+        .iter()
+        .chain(std::iter::once(&sphere));
+    for (a, b) in reached.clone().zip(reached.skip(1)) {
+        window.draw_line(&flat(a), &flat(b), &color);
+    }
+    window.draw_line(&sphere, &flat(&sphere), &color);
+}
+
+// Draw a bracket above each sequence span the animation has reached, with its label
+fn draw_spans(
+    window: &mut Window,
+    camera: &dyn Camera,
+    font: &Rc<Font>,
+    state: &AnimationState,
+    spans: &[Span],
+) {
+    let color = Point3::new(0.9, 0.8, 0.3);
+    let lift = |p: Point3<f32>| Point3::new(p.x, p.y + BRACKET_OFFSET, p.z);
+    let size = Vector2::new(window.width() as f32, window.height() as f32);
+
+    for span in spans {
+        if span.first > state.current_index || span.last >= state.path.len() {
+            continue;
+        }
+        let points = &state.path[span.first..=span.last];
 
-            trail_nodes.push(line); // Still need to add the invisible line
+        // End ticks and the bracket itself, following the path shape
+        window.draw_line(&points[0], &lift(points[0]), &color);
+        window.draw_line(
+            &points[points.len() - 1],
+            &lift(points[points.len() - 1]),
+            &color,
+        );
+        for pair in points.windows(2) {
+            window.draw_line(&lift(pair[0]), &lift(pair[1]), &color);
+        }
+
+        // Label at the middle of the bracket, if it is in front of the camera
+        let anchor = lift(points[points.len() / 2]);
+        if camera.view_transform().transform_point(&anchor).z < 0.0 {
+            let screen = camera.project(&anchor, &size);
+            window.draw_text(
+                &span.label,
+                &Point2::new(screen.x, size.y - screen.y),
+                40.0,
+                font,
+                &color,
+            );
+        }
+    }
+}
+
+fn cadence_color(kind: CadenceKind) -> Point3<f32> {
+    match kind {
+        CadenceKind::Perfect => Point3::new(1.0, 0.85, 0.35),
+        CadenceKind::Imperfect => Point3::new(0.95, 0.6, 0.3),
+        CadenceKind::Half => Point3::new(0.45, 0.8, 1.0),
+    }
+}
+
+// Ring each reached cadence arrival, facing the camera and labelled with its
+// kind; the ring flares out and whitens for a moment as it is reached
+fn draw_cadences(
+    window: &mut Window,
+    camera: &dyn Camera,
+    font: &Rc<Font>,
+    state: &AnimationState,
+    cadences: &[Cadence],
+) {
+    let size = Vector2::new(window.width() as f32, window.height() as f32);
+    let facing = camera.view_transform().rotation.inverse();
+    let (right, up) = (facing * Vector3::x(), facing * Vector3::y());
+    let now = state.elapsed();
+
+    for cadence in cadences {
+        if cadence.frame > state.current_index || cadence.frame >= state.path.len() {
+            continue;
+        }
+        let center = state.path[cadence.frame];
+        let since = now - state.starts[cadence.frame];
+        let flare = (1.0 - since / CADENCE_FLASH).clamp(0.0, 1.0);
+        let radius = CADENCE_RADIUS * (1.0 + CADENCE_FLARE * flare);
+        let color = cadence_color(cadence.kind);
+        let color = color + (Point3::new(1.0, 1.0, 1.0) - color) * flare;
+
+        let at = |side: usize| {
+            let angle = side as f32 / CADENCE_SIDES as f32 * std::f32::consts::TAU;
+            center + (right * angle.cos() + up * angle.sin()) * radius
+        };
+        for side in 0..CADENCE_SIDES {
+            window.draw_line(&at(side), &at(side + 1), &color);
         }
+
+        let anchor = center + up * radius;
+        if camera.view_transform().transform_point(&anchor).z < 0.0 {
+            let screen = camera.project(&anchor, &size);
+            window.draw_text(
+                cadence.kind.label(),
+                &Point2::new(screen.x, size.y - screen.y - LABEL_SIZE),
+                LABEL_SIZE,
+                font,
+                &color,
+            );
+        }
+    }
+}
+
+// Name each reached keyframe where the chord changes, newest first, skipping
+// labels closer than `spacing` pixels to one already drawn
+fn draw_labels(
+    window: &mut Window,
+    camera: &dyn Camera,
+    font: &Rc<Font>,
+    state: &AnimationState,
+    names: &[Option<String>],
+    spacing: f32,
+    color: Rgb,
+) {
+    let size = Vector2::new(window.width() as f32, window.height() as f32);
+    let reached = state.current_index.min(state.path.len() - 1);
+    let (r, g, b) = color;
+    let color = Point3::new(r, g, b);
+    let mut placed: Vec<Point2<f32>> = Vec::new();
+
+    for index in (0..=reached).rev() {
+        let Some(name) = names.get(index).and_then(Option::as_ref) else {
+            continue;
+        };
+        if index > 0 && names.get(index - 1) == names.get(index) {
+            continue;
+        }
+        let anchor = state.path[index];
+        if camera.view_transform().transform_point(&anchor).z >= 0.0 {
+            continue;
+        }
+        let screen = camera.project(&anchor, &size);
+        let at = Point2::new(screen.x, size.y - screen.y);
+        if placed.iter().any(|p| (p - at).norm() < spacing) {
+            continue;
+        }
+        window.draw_text(name, &at, LABEL_SIZE, font, &color);
+        placed.push(at);
+    }
+}
+
+// Colour the reached path by movement and label where each one begins
+fn draw_movements(
+    window: &mut Window,
+    camera: &dyn Camera,
+    font: &Rc<Font>,
+    state: &AnimationState,
+    movements: &[Span],
+) {
+    if movements.len() < 2 {
+        return;
     }
+    let size = Vector2::new(window.width() as f32, window.height() as f32);
+    let reached = state.current_index.min(state.path.len() - 1);
+
+    for (i, movement) in movements.iter().enumerate() {
+        if movement.first > reached {
+            break;
+        }
+        let (r, g, b) = MOVEMENT_COLORS[i % MOVEMENT_COLORS.len()];
+        let color = Point3::new(r, g, b);
+
+        // Include the step into the movement so segments join up
+        let first = movement.first.saturating_sub(1);
+        let last = movement.last.min(reached);
+        for pair in state.path[first..=last].windows(2) {
+            window.draw_line(&pair[0], &pair[1], &color);
+        }
+
+        let anchor = state.path[movement.first];
+        if camera.view_transform().transform_point(&anchor).z < 0.0 {
+            let screen = camera.project(&anchor, &size);
+            window.draw_text(
+                &movement.label,
+                &Point2::new(screen.x, size.y - screen.y),
+                40.0,
+                font,
+                &color,
+            );
+        }
+    }
+}
+
+// Draw reference paths and overlaid pieces as static labelled curves,
+// in load order; overlays sit where the path's projection puts them
+fn draw_references(
+    window: &mut Window,
+    camera: &dyn Camera,
+    font: &Rc<Font>,
+    references: &[ReferencePath],
+    overlays: &[OverlayPath],
+    state: &AnimationState,
+) {
+    let scale = state.scale;
+    let to_scene = |p: &[f32; 3]| Point3::new(p[0] * scale, p[1] * scale, p[2] * scale);
+    for (i, reference) in references.iter().enumerate() {
+        let points: Vec<Point3<f32>> = reference.points.iter().map(to_scene).collect();
+        draw_reference(window, camera, font, &reference.label, &points, i);
+    }
+    for (i, (overlay, points)) in overlays.iter().zip(&state.overlay_paths).enumerate() {
+        draw_reference(
+            window,
+            camera,
+            font,
+            &overlay.label,
+            points,
+            references.len() + i,
+        );
+    }
+}
+
+// One labelled curve in the `index`th reference color
+fn draw_reference(
+    window: &mut Window,
+    camera: &dyn Camera,
+    font: &Rc<Font>,
+    label: &str,
+    points: &[Point3<f32>],
+    index: usize,
+) {
+    let size = Vector2::new(window.width() as f32, window.height() as f32);
+    let (r, g, b) = REFERENCE_COLORS[index % REFERENCE_COLORS.len()];
+    let color = Point3::new(r, g, b);
+    for pair in points.windows(2) {
+        window.draw_line(&pair[0], &pair[1], &color);
+    }
+
+    // Label at the end of the path
+    if let Some(anchor) = points.last()
+        && camera.view_transform().transform_point(anchor).z < 0.0
+    {
+        let screen = camera.project(anchor, &size);
+        window.draw_text(
+            label,
+            &Point2::new(screen.x, size.y - screen.y),
+            40.0,
+            font,
+            &color,
+        );
+    }
+}
+
+// Body axes of the oriented sphere
+fn draw_orientation(window: &mut Window, center: Point3<f32>, rotation: UnitQuaternion<f32>) {
+    let length = SPHERE_RADIUS * 1.8;
+    let axes = [
+        (Vector3::x(), Point3::new(1.0, 0.3, 0.3)),
+        (Vector3::y(), Point3::new(0.3, 1.0, 0.3)),
+        (Vector3::z(), Point3::new(0.3, 0.3, 1.0)),
+    ];
+    for (axis, color) in axes {
+        window.draw_line(&center, &(center + rotation * axis * length), &color);
+    }
+}
+
+// Chord-space speed of every motion, in semitones of xyz distance per beat
+fn energy_profile(motions: &[MotionVec], durations: &[f32]) -> Vec<f32> {
+    motions
+        .iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let distance = (m[1] * m[1] + m[2] * m[2] + m[3] * m[3]).sqrt();
+            let beats = durations.get(i).copied().unwrap_or(1.0) / 4.0;
+            distance / beats.max(1e-3)
+        })
+        .collect()
+}
+
+// Scrolling graph of the energy profile along the bottom of the window,
+// with the current motion at the playhead
+fn draw_energy(window: &mut Window, state: &AnimationState, energy: &[f32], peak: f32, lift: f32) {
+    let (w, h) = (window.width() as f32, window.height() as f32);
+    let left = -w / 2.0 + ENERGY_MARGIN;
+    let right = w / 2.0 - ENERGY_MARGIN;
+    let bottom = -h / 2.0 + ENERGY_MARGIN + lift;
+    let top = bottom + ENERGY_HEIGHT;
+    let frame = Point3::new(0.3, 0.3, 0.4);
+
+    // Panel frame
+    let corners = [
+        Point2::new(left, bottom),
+        Point2::new(right, bottom),
+        Point2::new(right, top),
+        Point2::new(left, top),
+    ];
+    for i in 0..4 {
+        window.draw_planar_line(&corners[i], &corners[(i + 1) % 4], &frame);
+    }
+
+    // Keep the playhead a quarter of the way in once the piece gets going
+    let now = state.current_index as f32 + state.transition_progress;
+    let first = (now - ENERGY_WINDOW as f32 / 4.0).max(0.0);
+    let step = (right - left) / ENERGY_WINDOW as f32;
+    let to_screen = |x: f32, e: f32| {
+        Point2::new(
+            left + (x - first) * step,
+            bottom + e / peak.max(1e-3) * ENERGY_HEIGHT,
+        )
+    };
+
+    let start = first as usize;
+    let end = (start + ENERGY_WINDOW + 1).min(energy.len());
+    let color = Point3::new(0.9, 0.6, 0.2);
+    for i in start..end {
+        // Flat step per motion so peaks line up with their moves
+        let a = to_screen((i as f32).max(first), energy[i]);
+        let b = to_screen(
+            (i as f32 + 1.0).min(first + ENERGY_WINDOW as f32),
+            energy[i],
+        );
+        window.draw_planar_line(&a, &b, &color);
+        if let Some(&next) = energy.get(i + 1)
+            && i + 1 < end
+        {
+            window.draw_planar_line(&b, &to_screen(i as f32 + 1.0, next), &color);
+        }
+    }
+
+    // Playhead
+    let playhead = left + (now - first) * step;
+    window.draw_planar_line(
+        &Point2::new(playhead, bottom),
+        &Point2::new(playhead, top),
+        &Point3::new(1.0, 1.0, 1.0),
+    );
+}
+
+// Register everything clickable drawn this frame, keyframes sized like their marker cubes when shown
+fn register_pickables(
+    picker: &mut Picker,
+    state: &AnimationState,
+    details: &Details,
+    markers: Option<&Markers>,
+    voices: &[Point3<f32>],
+    origin: Option<f32>,
+) {
+    picker.clear();
+    picker.register(
+        SceneObject::Sphere,
+        state.interpolated_position(),
+        SPHERE_RADIUS,
+    );
+
+    let reached = state.current_index.min(state.path.len() - 1);
+    for (index, point) in state.path[..=reached].iter().enumerate() {
+        let size = markers.map_or(0.0, |markers| markers.sizes[index]);
+        let radius = KEYFRAME_PICK_RADIUS.max(size * 3.0_f32.sqrt());
+        picker.register(SceneObject::Keyframe(index), *point, radius);
+    }
+
+    for (index, cadence) in details.cadences.iter().enumerate() {
+        if cadence.frame <= reached {
+            let center = state.path[cadence.frame];
+            picker.register(SceneObject::Cadence(index), center, CADENCE_RADIUS);
+        }
+    }
+
+    for (index, span) in details.spans.iter().enumerate() {
+        if span.first <= state.current_index && span.last < state.path.len() {
+            let mid = state.path[(span.first + span.last) / 2];
+            let anchor = Point3::new(mid.x, mid.y + BRACKET_OFFSET, mid.z);
+            picker.register(SceneObject::Span(index), anchor, KEYFRAME_PICK_RADIUS * 2.0);
+        }
+    }
+
+    for (voice, &center) in voices.iter().enumerate() {
+        picker.register(SceneObject::Voice(voice), center, VOICE_RADIUS);
+    }
+    if let Some(radius) = origin {
+        picker.register(SceneObject::Origin, Point3::origin(), radius);
+    }
+}
+
+// What a click can tell about the piece
+struct Details<'a> {
+    chords: &'a [[i32; 4]],              // Voice pitches of every keyframe
+    bars: &'a [usize],                   // Bar in which every keyframe starts
+    beats: &'a [f32],                    // Beat at which every keyframe starts
+    spans: &'a [Span],                   // Labelled keyframe ranges
+    cadences: &'a [Cadence],             // Detected phrase endings
+    motion_types: &'a [[MotionType; 6]], // Voice-pair motion of each step
+}
+
+// Human-readable description of a picked object
+fn describe(object: SceneObject, state: &AnimationState, details: &Details) -> String {
+    let Details {
+        chords,
+        bars,
+        beats,
+        spans,
+        cadences,
+        motion_types,
+    } = details;
+    let keyframe = |index: usize| {
+        let notes = chords.get(index).copied().unwrap_or_default();
+        let bar = bars.get(index).copied().unwrap_or(1);
+        let beat = beats.get(index).copied().unwrap_or(1.0);
+        let name = analysis::detect_chord(notes).map_or("?".to_string(), |c| c.to_string());
+        // How the voices moved to get here
+        let arrival = index
+            .checked_sub(1)
+            .and_then(|step| motion_types.get(step))
+            .map_or(String::new(), |types| {
+                format!(" (motion {})", transformation::motion_label(types))
+            });
+        format!(
+            "keyframe {index} (bar {bar}:{}): {notes:?} {name}{arrival}",
+            format_beat(beat)
+        )
+    };
+    match object {
+        SceneObject::Sphere => format!("sphere at {}", keyframe(state.current_index)),
+        SceneObject::Voice(voice) => {
+            let pitch = chords
+                .get(state.current_index)
+                .map_or(0, |chord| chord[voice]);
+            format!(
+                "voice {} (pitch {pitch}) at {}",
+                voice + 1,
+                keyframe(state.current_index)
+            )
+        }
+        SceneObject::Origin => "origin of chord space".to_string(),
+        SceneObject::Keyframe(index) => keyframe(index),
+        SceneObject::Span(index) => {
+            let span = &spans[index];
+            format!("{} (keyframes {}-{})", span.label, span.first, span.last)
+        }
+        SceneObject::Cadence(index) => {
+            let cadence = &cadences[index];
+            format!(
+                "{} {} -> {} at {}",
+                cadence.kind.label(),
+                cadence.from,
+                cadence.to,
+                keyframe(cadence.frame)
+            )
+        }
+    }
+}
+
+// Beat within the bar, as a whole number when it falls on one
+fn format_beat(beat: f32) -> String {
+    let text = format!("{beat:.2}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// Switch to the next 4D projection, saying how much PCA captures
+fn cycle_projection(state: &mut AnimationState, hud: &mut Hud) {
+    state.projection = state.projection.next();
+    state.reproject();
+    if state.projection == Projection::Pca {
+        let (_, share) = projection::principal_axes(&state.points);
+        hud.notify(format!(
+            "Projection: pca ({:.0}% of the motion variance)",
+            share * 100.0
+        ));
+    } else {
+        hud.notify(format!("Projection: {}", state.projection.name()));
+    }
+}
+
+// What the scene's x, y and z show under a projection
+fn scene_axes(axes: &[String; 4], projection: Projection) -> [String; 3] {
+    match projection {
+        Projection::DropW | Projection::Perspective | Projection::Stereographic => {
+            [axes[1].clone(), axes[2].clone(), axes[3].clone()]
+        }
+        Projection::Pca => [1, 2, 3].map(|n| format!("principal component {n}")),
+    }
+}
+
+// Panel slider positions (0..1) for the playback speed, trail and color
+// scale, and back; the trail and color scale sliders are logarithmic and
+// the trail slider's far end keeps the whole trail
+fn speed_fraction(speed: f32) -> f32 {
+    let level = SPEED_LEVELS
+        .iter()
+        .position(|&level| level >= speed)
+        .unwrap_or(SPEED_LEVELS.len() - 1);
+    level as f32 / (SPEED_LEVELS.len() - 1) as f32
+}
+
+fn speed_at(fraction: f32) -> f32 {
+    SPEED_LEVELS[(fraction * (SPEED_LEVELS.len() - 1) as f32).round() as usize]
+}
+
+fn trail_fraction(trail: Trail) -> f32 {
+    match trail {
+        Trail::Last(n) => log_fraction(n as f32, TRAIL_RANGE).min(0.99),
+        Trail::All | Trail::Decay(_) | Trail::Piece => 1.0,
+    }
+}
+
+fn trail_at(fraction: f32) -> Trail {
+    if fraction >= 1.0 {
+        Trail::All
+    } else {
+        Trail::Last(log_value(fraction, TRAIL_RANGE).round() as usize)
+    }
+}
+
+fn log_fraction(value: f32, (low, high): (f32, f32)) -> f32 {
+    ((value / low).ln() / (high / low).ln()).clamp(0.0, 1.0)
+}
+
+fn log_value(fraction: f32, (low, high): (f32, f32)) -> f32 {
+    low * (high / low).powf(fraction)
+}
+
+// Keyframe positions in the scene at the start of playback, each with the
+// sphere's colour on arrival there
+pub fn trajectory(
+    origin: MotionVec,
+    motions: &[MotionVec],
+    chords: &[[i32; 4]],
+    config: &Config,
+) -> (Vec<[f32; 3]>, Vec<Rgb>) {
+    let mut state = AnimationState::new(
+        origin,
+        motions.to_vec(),
+        Vec::new(),
+        Vec::new(),
+        config.projection,
+        config.smoothing,
+        config.easing,
+    );
+    state.set_scale(config.position_scale, grid_width(config));
+    state.set_coloring(config.coloring, chords);
+    let points = state.path.iter().map(|p| [p.x, p.y, p.z]).collect();
+    let colors = (0..state.path.len())
+        .map(|i| config.palette.at(state.keyframe_hue(i)))
+        .collect();
+    (points, colors)
+}
+
+// Edge to edge width of the reference grid as configured
+fn grid_width(config: &Config) -> f32 {
+    2.0 * config.grid_size * config.grid_cells as f32
+}
+
+// Timestamped file name next to the input (inside it when it is a folder)
+fn output_path(input: &Path, extension: &str) -> PathBuf {
+    let (dir, stem) = if input.is_dir() {
+        (input.to_path_buf(), "chord-space".to_string())
+    } else {
+        (
+            input.parent().map(Path::to_path_buf).unwrap_or_default(),
+            input
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "chord-space".into()),
+        )
+    };
+
+    // UTC date and time from the Unix clock (civil-from-days)
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs()) as i64;
+    let (days, time) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    let stamp = format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    );
+
+    // Never overwrite an earlier file written within the same second
+    let mut path = dir.join(format!("{stem}-{stamp}.{extension}"));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{stem}-{stamp}-{n}.{extension}"));
+        n += 1;
+    }
+    path
+}
+
+// Write a recorded camera path next to the input
+fn save_camera_path(take: &CameraPath, input: &Path, hud: &mut Hud) {
+    let path = output_path(input, "camera.json");
+    match take.save(&path) {
+        Ok(()) => {
+            println!("[^.^] Wrote camera path to {:?}", path);
+            hud.notify(format!("Saved camera path to {}", path.display()));
+        }
+        Err(e) => eprintln!("[-.-] Could not write camera path: {e}"),
+    }
+}
+
+// Open the window, reporting failure instead of panicking inside kiss3d
+fn open_window(title: &str, config: &Config) -> Result<Window, String> {
+    let hidden = config.hidden;
+    if cfg!(target_os = "linux")
+        && std::env::var_os("DISPLAY").is_none()
+        && std::env::var_os("WAYLAND_DISPLAY").is_none()
+    {
+        // A hidden window still needs an X server; a virtual one will do
+        let hint = if hidden {
+            ", try running under xvfb-run"
+        } else {
+            ""
+        };
+        return Err(format!(
+            "no display available (DISPLAY and WAYLAND_DISPLAY are unset{hint})"
+        ));
+    }
+
+    // Silence the panic message while trying; the caller prints its own
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(|| {
+        let (width, height) = config.window_size;
+        let samples = match config.msaa {
+            2 => NumSamples::Two,
+            4 => NumSamples::Four,
+            8 => NumSamples::Eight,
+            16 => NumSamples::Sixteen,
+            _ => NumSamples::Zero,
+        };
+        let setup = CanvasSetup {
+            vsync: config.vsync,
+            samples,
+        };
+        let mut window = Window::new_with_setup(title, width, height, setup);
+        if hidden {
+            window.hide();
+        }
+        window
+    });
+    panic::set_hook(hook);
+
+    result.map_err(|e| {
+        e.downcast_ref::<String>()
+            .cloned()
+            .or_else(|| e.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| "unknown error".into())
+    })
+}
+
+// Blend antialiased lines into the frame. Text drawing turns blending off
+// again when it is done, so this is needed before every frame.
+fn smooth_lines() {
+    let ctxt = Context::get();
+    ctxt.enable(LINE_SMOOTH);
+    ctxt.enable(Context::BLEND);
+    ctxt.blend_func_separate(
+        Context::SRC_ALPHA,
+        Context::ONE_MINUS_SRC_ALPHA,
+        Context::ONE,
+        Context::ONE_MINUS_SRC_ALPHA,
+    );
+}
+
+// Minimal text renderer: the keyframe path seen from the front (x right,
+// y up), numbered every few keyframes so the order can be followed
+pub fn render_text(origin: MotionVec, motions: &[MotionVec]) {
+    let mut points = vec![(origin[1], origin[2])];
+    for m in motions {
+        let (x, y) = points[points.len() - 1];
+        points.push((x + m[1], y + m[2]));
+    }
+
+    let (cols, rows) = TEXT_VIEW_SIZE;
+    let min_x = points.iter().map(|p| p.0).fold(f32::INFINITY, f32::min);
+    let max_x = points.iter().map(|p| p.0).fold(f32::NEG_INFINITY, f32::max);
+    let min_y = points.iter().map(|p| p.1).fold(f32::INFINITY, f32::min);
+    let max_y = points.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+    let cell = |p: (f32, f32)| {
+        let col = (p.0 - min_x) / (max_x - min_x).max(1.0) * (cols - 1) as f32;
+        let row = (max_y - p.1) / (max_y - min_y).max(1.0) * (rows - 1) as f32;
+        (col.round() as usize, row.round() as usize)
+    };
+
+    let mut canvas = vec![vec![' '; cols]; rows];
+    for &point in &points {
+        let (col, row) = cell(point);
+        canvas[row][col] = if canvas[row][col] == ' ' { '·' } else { '*' }; // * = revisited
+    }
+    // Endpoints last so they stay visible
+    let (col, row) = cell(points[0]);
+    canvas[row][col] = 'S';
+    let (col, row) = cell(points[points.len() - 1]);
+    canvas[row][col] = 'E';
+
+    println!("\n🖥️ Text view (x right, y up; S start, E end, * revisited):");
+    println!("+{}+", "-".repeat(cols));
+    for row in canvas {
+        println!("|{}|", row.into_iter().collect::<String>());
+    }
+    println!("+{}+", "-".repeat(cols));
+    println!("x: {min_x:.1}..{max_x:.1}, y: {min_y:.1}..{max_y:.1} (semitones of motion)");
 }
 
 // Render function
-pub fn render(transformation: Vec<[i32; 4]>) {
-    if transformation.is_empty() {
+pub fn render(piece: Piece, config: &Config) {
+    let Piece {
+        chords,
+        origin,
+        motions,
+        durations,
+        seconds,
+        bars,
+        beats,
+        timings,
+        spans,
+        cadences,
+        references,
+        overlays,
+        movements,
+        motion_types,
+        step_sizes,
+        axes: axis_names,
+    } = piece;
+    if motions.is_empty() {
         println!("No transformation data to render");
         return;
     }
 
-    // Create window
-    let mut window = Window::new("MIDI Visualization - Press ESC to exit");
+    // Create window, or fall back to the text view when there is no GL context
+    // and nothing is being recorded
+    let mut window = match open_window("MIDI Visualization - Press ESC to exit", config) {
+        Ok(window) => window,
+        Err(e) => {
+            eprintln!("[-.-] Could not open a window: {e}");
+            if config.render_video.is_some()
+                || config.export_gif.is_some()
+                || config.frame_dir.is_some()
+                || config.poster.is_some()
+            {
+                eprintln!("[-.-] Recording needs a window, nothing was recorded");
+                std::process::exit(1);
+            }
+            eprintln!("[-.-] Falling back to text mode");
+            render_text(origin, &motions);
+            return;
+        }
+    };
+
+    let (r, g, b) = config.theme.background;
+    window.set_background_color(r, g, b);
 
-    // Set background color (dark blue)
-    window.set_background_color(0.05, 0.05, 0.1);
+    // Gradient sky, added first so the rest of the scene draws over it
+    let mut sky = config
+        .sky
+        .then(|| sky::add_sky(&mut window, config.theme.sky));
 
-    // Add a light
-    window.set_light(Light::StickToCamera);
+    // Add a light, with the theme's fill and sphere lights beside it
+    window.set_light(config.theme.lighting.light());
+    let shading = Shading::new(config.theme.intensity);
 
     // Create sphere
-    let mut sphere = window.add_sphere(30.0);
+    let mut sphere = window.add_sphere(SPHERE_RADIUS);
     sphere.set_color(1.0, 0.0, 0.0); // Initial color, will be updated
+    sphere
+        .data_mut()
+        .get_object_mut()
+        .set_user_data(Box::new(config.finish));
+    let mut wireframe = config.wireframe;
 
-    // Create grid
-    let _grid = create_grid(&mut window);
+    // Tetrahedron glyph shaped by the voicing (cycle glyphs with G)
+    let mut glyph_mode = config.glyph;
+    let mut tetrahedron = glyph::add_tetrahedron(&mut window);
+    tetrahedron.set_visible(glyph_mode.shows_tetrahedron());
+    sphere.set_visible(glyph_mode.shows_sphere());
 
-    // Storage for trail nodes
-    let mut trail_nodes: Vec<SceneNode> = Vec::new();
+    // Glow around bright objects (toggle with B)
+    let mut bloom = Bloom::new();
+    let mut show_bloom = config.bloom;
+
+    // Red/cyan stereo, one eye per frame (toggle with Y); replaces bloom
+    let mut anaglyph = Anaglyph::new();
+    let mut show_anaglyph = config.anaglyph;
+
+    // Bursts on chord changes, sized by the total voice motion of each step
+    let mut particles = Particles::default();
+    let leaps = transformation::voice_leading_sizes(&chords, Metric::Taxicab);
+
+    // One small sphere per voice (toggle with V)
+    let mut show_voices = config.voices;
+    let mut voice_spheres: Vec<SceneNode> = (0..4)
+        .map(|_| {
+            let mut node = window.add_sphere(VOICE_RADIUS);
+            node.set_visible(show_voices);
+            node
+        })
+        .collect();
+
+    // Font for in-scene labels
+    let font = Font::default();
+
+    // Harmonic energy graph (toggle with E)
+    let energy = energy_profile(&motions, &durations);
+    let peak = energy.iter().copied().fold(0.0, f32::max);
+    let mut show_energy = config.energy;
+
+    // Playback readout and notices (toggle with H)
+    let mut hud = Hud::new(config.theme.text);
+    let mut show_hud = true;
+
+    // Frame timing and scene size readout (toggle with F3)
+    let mut show_stats = config.stats;
+    let mut frame_timer = FrameTimer::new();
+    let total_time: f32 = seconds.iter().sum();
+
+    // Chord names along the path (toggle with L)
+    let names: Vec<Option<String>> = chords
+        .iter()
+        .map(|&chord| analysis::detect_chord(chord).map(|name| name.to_string()))
+        .collect();
+    let mut show_labels = config.labels;
+
+    // Reference grid and origin arrows (toggle with Q), the grid's cells resized with F7/F8 and its
+    // extent changed with F9/F10; with --no-grid it shows in the text color
+    let mut show_grid = config.theme.grid.is_some();
+    let grid_color = config.theme.grid.unwrap_or(config.theme.text);
+    let mut grid_cell = config.grid_size;
+    let mut grid_cells = config.grid_cells;
+
+    // Dim preview of the whole path (toggle with F2)
+    let mut show_ghost = config.ghost;
+
+    // Trail shadow on the grid plane (toggle with D)
+    let mut show_shadow = config.shadow;
+
+    // Bar lines marked on the trail (toggle with J)
+    let mut show_downbeats = config.downbeats;
+
+    // Voice timelines under the scene (toggle with M)
+    let mut show_roll = config.piano_roll;
+
+    // Labelled axes, orientation gizmo and hue legend (toggle with A)
+    let mut show_axes = config.axes;
+
+    // Quaternion orientation of the sphere (toggle with O)
+    let mut show_orientation = config.orientation;
 
     // Initialize animation state
-    let mut state = AnimationState::new(transformation);
+    let mut state = AnimationState::new(
+        origin,
+        motions,
+        seconds,
+        timings,
+        config.projection,
+        config.smoothing,
+        config.easing,
+    );
+    state.set_overlays(
+        overlays
+            .iter()
+            .map(|overlay| keyframe_points(overlay.origin, &overlay.motions, config.smoothing))
+            .collect(),
+    );
+    state.set_scale(config.position_scale, grid_width(config));
+    state.set_coloring(config.coloring, &chords);
+
+    // A cube at every keyframe reached (toggle with Z)
+    let markers = Markers::new(&state, &chords, config.palette);
+    let mut show_markers = config.markers;
+
+    // Cross-section by a hyperplane of constant w in place of the trail
+    // (toggle with X, move it with , and .); slicing uses drop-w so the
+    // sphere sits in the section's coordinates
+    let middle_w = |state: &AnimationState| {
+        let (low, high) = state.w_extent();
+        (low + high) / 2.0
+    };
+    let mut slice = config.slice.then(|| middle_w(&state));
+    if slice.is_some() {
+        state.projection = Projection::DropW;
+        state.reproject();
+    }
 
     // Create camera
     let eye = Point3::new(0.0, 200.0, 500.0);
     let at = Point3::new(0.0, 0.0, 0.0);
     let mut camera = ArcBall::new(eye, at);
 
-    // Animation loop
+    // Free-fly camera (toggle with F), flown with the arrow keys
+    let mut fly = FirstPerson::new(eye, at);
+    fly.set_move_step(FLY_STEP);
+    let mut flying = false;
+
+    // Canonical views on the number keys 1-6, glided to rather than snapped
+    let home = View::of(&camera);
+    let mut glide: Option<Glide> = None;
+
+    // Cinematic turn around the trajectory (toggle with W)
+    let orbit = Orbit {
+        speed: config.orbit_speed.to_radians(),
+        elevation: config.orbit_elevation.to_radians(),
+    };
+    let mut orbiting = config.orbit;
+
+    // Camera kept on the sphere and its recent trail (toggle with F4)
+    let auto_frame = Framing {
+        seconds: config.frame_seconds,
+    };
+    let mut framing = config.auto_frame;
+
+    // Orbit camera recorded over piece time (start and stop with Shift+R)
+    // and replayed from --camera-path, including into recordings
+    let mut camera_take: Option<CameraPath> = None;
+    let replay = config
+        .camera_path
+        .as_ref()
+        .and_then(|path| match CameraPath::load(path) {
+            Ok(replay) if !replay.is_empty() => {
+                println!("[^.^] Replaying camera path {:?}", path);
+                Some(replay)
+            }
+            Ok(_) => {
+                eprintln!("[-.-] Camera path {:?} has no samples", path);
+                None
+            }
+            Err(e) => {
+                eprintln!("[-.-] Could not load camera path {:?}: {e}", path);
+                None
+            }
+        });
+
+    // The chords sounded on a MIDI port as the sphere reaches them
+    let mut midi_out = config
+        .midi_out
+        .as_ref()
+        .and_then(|path| match MidiOut::open(path) {
+            Ok(out) => {
+                println!("[^.^] Sending chords to MIDI port {:?}", path);
+                Some(out)
+            }
+            Err(e) => {
+                eprintln!("[-.-] Could not open MIDI port: {e}");
+                None
+            }
+        });
+
+    // Voice-leading distance shells around the chord reached (toggle with I)
+    let mapper = mapping::create(&config.mapping, config).expect("mapping checked by config");
+    let mut isocontours = Isocontours::new(&mut window, config.isocontours);
+    let mut isocontour_basis = None;
+    window.set_point_size(4.0);
+
+    // 4D rotation speed (adjust with [ and ], 0 stops it)
+    let mut rotation_speed = config.rotation_speed;
+
+    // Click picking
+    let mut picker = Picker::default();
+    let mut cursor = (0.0f64, 0.0f64);
+    let mut press_at: Option<(f64, f64)> = None;
+    let mut picked: Option<SceneObject> = None; // Described in the HUD until a click elsewhere
+    let details = Details {
+        chords: &chords,
+        bars: &bars,
+        beats: &beats,
+        spans: &spans,
+        cadences: &cadences,
+        motion_types: &motion_types,
+    };
+
+    // Pause with Space
+    let mut paused = config.paused;
+
+    // One keyframe per key press (toggle with T; N and P step)
+    let mut stepping = config.step;
+    let mut step_to: Option<usize> = None; // Keyframe being glided to
+
+    // Start over at the end (toggle with K); recordings always stop there
+    let mut looping = config.looping;
+    let mut looped = false;
+
+    // Sliders and buttons for the main settings (toggle with Tab)
+    let mut panel = Panel::default();
+    panel.visible = config.panel;
+    let mut on_panel = false; // Mouse button went down over the panel
+    let mut trail = config.trail;
+    let mut trail_colors = TrailColors::new(&state, &step_sizes, config.palette);
+
+    // Tube around the trail, thicker for bigger leaps (toggle with U)
+    let mut tube = Tube::new(&leaps, config.palette);
+    let mut show_tube = config.tube;
+    let mut tube_basis = None; // Projection, rotation and coloring it was built for
+
+    // Playback speed (+/- step through SPEED_LEVELS), shown in the title
+    let mut playback_speed = config.speed;
+    let title = |speed: f32| format!("MIDI Visualization - {speed}x - Press ESC to exit");
+    window.set_title(&title(playback_speed));
+
+    // First keyframe of the bar after / of the bar containing a keyframe
+    let next_bar = |index: usize| {
+        let bar = bars.get(index).copied().unwrap_or(0);
+        (index..bars.len())
+            .find(|&i| bars[i] > bar)
+            .unwrap_or(bars.len().saturating_sub(1))
+    };
+    let bar_start = |index: usize| {
+        let bar = bars.get(index).copied().unwrap_or(0);
+        (0..=index.min(bars.len().saturating_sub(1)))
+            .find(|&i| bars[i] == bar)
+            .unwrap_or(0)
+    };
+
+    // Video, GIF and frame export step the animation at a fixed frame rate
+    let mut recorder = match (&config.render_video, &config.export_gif, &config.frame_dir) {
+        (Some(path), _, _) => Some(Recorder::new(path, config.fps)),
+        (None, Some(path), _) => Some(Recorder::gif(path, config.fps, config.gif_size)),
+        (None, None, Some(dir)) => match Recorder::sequence(dir, config.fps) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                eprintln!("[-.-] Could not export frames: {e}");
+                return;
+            }
+        },
+        (None, None, None) => None,
+    };
+
+    // Recording a bar range starts at its first keyframe
+    if let Some((first, last)) = config.bars {
+        match bars.iter().position(|&bar| (first..=last).contains(&bar)) {
+            Some(index) => state.seek(index),
+            None => {
+                eprintln!("[-.-] The piece has no keyframes in bars {first}-{last}");
+                return;
+            }
+        }
+    }
+
+    // A poster is the finished piece, framed whole at a three-quarter angle
+    // with its chord names, taken once the scene has been laid out
+    let mut poster_frames = 0;
+    if config.poster.is_some() {
+        state.seek(state.motions.len() - 1);
+        state.transition_progress = 1.0;
+        paused = true;
+        show_labels = true;
+        show_hud = false;
+        panel.visible = false;
+        orbiting = false;
+        framing = false;
+        let angled = camera::preset(4, View::of(&camera), &state.path, home);
+        if let Some(view) = angled.and_then(|view| camera::preset(6, view, &state.path, home)) {
+            view.apply(&mut camera);
+        }
+    }
+
+    // Animation loop: the animation advances in fixed ticks of the clock,
+    // which runs on wall time live and at exactly 1/fps per frame when
+    // recording, so playback does not depend on how fast frames render
     let mut last_time = std::time::Instant::now();
+    let mut clock = 0.0_f64; // Seconds of animation time due
+    let mut ticks = 0_u64; // Ticks run so far
+    let mut last_pose = Pose::of(&state, config.palette, &chords);
     let mut running = true;
 
-    while window.render_with_camera(&mut camera) && running {
+    while running {
+        shading.apply(&mut window);
+        let view: &mut dyn Camera = if flying { &mut fly } else { &mut camera };
+        if let Some(sky) = &mut sky {
+            sky::follow(sky, view);
+        }
+        if config.smooth_lines {
+            smooth_lines();
+        }
+        let open = if show_anaglyph {
+            let mut eye = Eye::new(view, anaglyph.left, state.interpolated_position());
+            window.render_with_camera_and_effect(&mut eye, &mut anaglyph)
+        } else if show_bloom {
+            window.render_with_camera_and_effect(view, &mut bloom)
+        } else {
+            window.render_with_camera(view)
+        };
+        if !open {
+            break;
+        }
+
+        // Capture what was just shown, once the animation has started
+        if let Some(recorder) = &mut recorder
+            && state.timer > 0.0
+            && let Err(e) = recorder.frame(&window)
+        {
+            eprintln!("[-.-] Stopped recording: {e}");
+            running = false;
+        }
+        if let Some(path) = &config.poster {
+            poster_frames += 1;
+            if poster_frames == POSTER_FRAME {
+                match capture::still(&window, path) {
+                    Ok(()) => println!("[^.^] Saved poster to {:?}", path),
+                    Err(e) => eprintln!("[-.-] Could not save poster: {e}"),
+                }
+                running = false;
+            }
+        }
+
         // Calculate delta time
         let now = std::time::Instant::now();
-        let delta_time = now.duration_since(last_time).as_secs_f32();
+        frame_timer.record(now.duration_since(last_time).as_secs_f32());
+        let delta_time = match recorder {
+            Some(_) => 1.0 / config.fps as f32,
+            None => now.duration_since(last_time).as_secs_f32(),
+        };
         last_time = now;
+        clock += match recorder {
+            Some(_) => 1.0 / config.fps as f64,
+            None => delta_time as f64,
+        };
+
+        if let Some(move_to) = &mut glide
+            && !move_to.step(&mut camera, delta_time)
+        {
+            glide = None;
+        }
+        let recent = framing.then(|| state.recent_path(auto_frame.seconds));
+        if orbiting && glide.is_none() && !flying {
+            let around = recent.as_deref().unwrap_or(&state.path);
+            orbit.step(&mut camera, around, delta_time);
+        }
+        if let Some(recent) = &recent
+            && glide.is_none()
+            && !flying
+        {
+            auto_frame.step(&mut camera, recent, delta_time);
+        }
+        if let Some(replay) = &replay
+            && let Some(view) = replay.view_at(state.elapsed())
+        {
+            view.apply(&mut camera);
+        }
+        if let Some(take) = &mut camera_take
+            && !flying
+        {
+            take.record(state.elapsed(), View::of(&camera));
+        }
+
+        // Update animation state (the camera stays live while paused)
+        // Step mode only moves while gliding to the keyframe asked for,
+        // taking STEP_TIME per motion whatever its length in the piece
+        let tick = (1.0 / TICK_RATE) as f32;
+        let due = (clock * TICK_RATE + 1e-6) as u64;
+        if due - ticks > MAX_TICKS && recorder.is_none() {
+            // Fell far behind (a stall or a dragged window): skip ahead
+            ticks = due - MAX_TICKS;
+        }
+        let rotation_before = state.rotation;
+        while ticks < due && running {
+            ticks += 1;
+            last_pose = Pose::of(&state, config.palette, &chords);
+            let advance = match step_to {
+                _ if !stepping && paused => 0.0,
+                _ if !stepping => tick * playback_speed,
+                Some(target) if target > state.current_index => {
+                    let length = state.seconds.get(state.current_index).copied();
+                    tick * length.unwrap_or(STEP_TIME) / STEP_TIME
+                }
+                _ => 0.0,
+            };
+            if advance > 0.0 {
+                let before = state.current_index;
+                if !state.update(advance) {
+                    if looping && recorder.is_none() {
+                        state.seek(0);
+                        looped = true;
+                        hud.notify("Looping from the first keyframe".into());
+                    } else {
+                        running = false;
+                    }
+                }
+                particles.update(advance);
+
+                // Punctuate each new keyframe, bigger for bigger leaps
+                if config.particles && state.current_index != before {
+                    let distance = leaps.get(before).copied().unwrap_or(0.0);
+                    let (r, g, b) = state.interpolated_color(config.palette);
+                    particles.burst(state.current_position, distance, (r, g, b));
+                }
+            }
+
+            // Stop recording once the selected bars have been played
+            if let Some((_, last)) = config.bars
+                && bars.get(state.current_index).is_some_and(|&bar| bar > last)
+            {
+                running = false;
+            }
+            if !paused {
+                state.rotation += rotation_speed * tick;
+            }
+        }
+        if state.rotation != rotation_before {
+            state.reproject();
+        }
 
-        // Update animation state
-        running = state.update(delta_time);
+        // Keep the MIDI port on the keyframe reached, silent while paused
+        if let Some(out) = &mut midi_out {
+            let reached = (!paused).then(|| state.current_index.min(chords.len() - 1));
+            if let Err(e) = out.follow(reached, &chords) {
+                eprintln!("[-.-] Stopped MIDI output: {e}");
+                midi_out = None;
+            }
+        }
+        let alpha = (clock * TICK_RATE - ticks as f64).clamp(0.0, 1.0) as f32;
+        let pose = last_pose.blend(Pose::of(&state, config.palette, &chords), alpha);
 
         // Get current position and color
-        let position = state.interpolated_position();
-        let (r, g, b) = state.interpolated_color();
+        let position = pose.position;
+        let (r, g, b) = pose.color;
 
         // Update sphere position and color
         sphere.set_local_translation(Translation3::new(position.x, position.y, position.z));
         sphere.set_color(r, g, b);
+        shading.follow(position, (r, g, b));
+        if config.pulse {
+            let scale = state.pulse();
+            sphere.set_local_scale(scale, scale, scale);
+        }
+
+        let pitches = pose.voices;
+        if glyph_mode.shows_tetrahedron() {
+            let corners = glyph::update_tetrahedron(&mut tetrahedron, pitches);
+            tetrahedron
+                .set_local_translation(Translation3::new(position.x, position.y, position.z));
+            tetrahedron.set_color(r, g, b);
+            // Mark which corner is which voice
+            for (corner, &(vr, vg, vb)) in corners.iter().zip(&config.voice_colors) {
+                window.draw_point(&(position + corner.coords), &Point3::new(vr, vg, vb));
+            }
+        }
 
-        // Update trail
-        update_trail(&mut window, &state, &mut trail_nodes);
+        // Voices stand beside the sphere, pitch as height, so crossings show
+        let mut voice_positions = Vec::new();
+        if show_voices {
+            let mut previous: Option<Point3<f32>> = None;
+            for (v, node) in voice_spheres.iter_mut().enumerate() {
+                let at = Point3::new(
+                    position.x + VOICE_OFFSET + v as f32 * VOICE_SPACING,
+                    position.y + (pitches[v] - transformation::MIDDLE_C[v] as f32) * VOICE_HEIGHT,
+                    position.z,
+                );
+                let (vr, vg, vb) = config.voice_colors[v];
+                node.set_local_translation(Translation3::new(at.x, at.y, at.z));
+                node.set_color(vr, vg, vb);
+                if let Some(previous) = previous {
+                    window.draw_line(&previous, &at, &Point3::new(r, g, b));
+                }
+                previous = Some(at);
+                voice_positions.push(at);
+            }
+        }
+
+        // Spin the sphere by the 4-vector and show its axes
+        if show_orientation {
+            let rotation = state.interpolated_orientation();
+            sphere.set_local_rotation(rotation);
+            draw_orientation(&mut window, position, rotation);
+        } else {
+            sphere.set_local_rotation(UnitQuaternion::identity());
+        }
+
+        let wire = wireframe;
+        sphere.set_surface_rendering_activation(!wire);
+        sphere.set_lines_width(if wire { 1.0 } else { 0.0 });
+        if isocontours.visible() {
+            let index = state.current_index.min(chords.len() - 1);
+            let basis = (index, state.projection, state.rotation, state.scale);
+            if isocontour_basis != Some(basis) {
+                let around = isocontour::neighbours(&*mapper, chords[index], state.points[index]);
+                let placed = around.iter().map(|points| state.place(points)).collect();
+                isocontours.update(state.path[index], placed);
+                isocontour_basis = Some(basis);
+            }
+        }
+        isocontours.draw(&mut window);
+
+        particles.draw(&mut window);
+        if show_grid {
+            draw_grid(
+                &mut window,
+                grid_color,
+                config.theme.background,
+                grid_cell,
+                grid_cells,
+            );
+            let view: &dyn Camera = if flying { &fly } else { &camera };
+            axes::draw_origin(&mut window, view, config.theme.text);
+        }
+
+        // Update trail, over the dimmed earlier pass when looping keeps it
+        if looped && config.keep_trail {
+            draw_previous_pass(&mut window, &state, &trail_colors);
+        }
+        let view: &dyn Camera = if flying { &fly } else { &camera };
+        match slice {
+            Some(level) => draw_slice(&mut window, &state, &trail_colors, level),
+            // A poster shows every step at full color and detail
+            None if config.poster.is_some() => {
+                let whole = (1, state.path.len() - 1);
+                draw_path(
+                    &mut window,
+                    &state,
+                    &Frustum::of(view),
+                    whole,
+                    false,
+                    |step| Some(trail_colors.at(step, 1.0)),
+                );
+            }
+            None => draw_trail(
+                &mut window,
+                view,
+                &state,
+                &trail_colors,
+                trail,
+                show_ghost,
+                config.theme.intensity.sphere,
+            ),
+        }
+        if show_markers {
+            markers.draw(&mut window, &state);
+        }
+        if show_downbeats && slice.is_none() {
+            draw_downbeats(&mut window, &state, &bars, trail, config.theme.text);
+        }
+        if show_tube {
+            let basis = (
+                state.projection,
+                state.rotation,
+                state.color_scale,
+                state.coloring,
+            );
+            if tube_basis != Some(basis) {
+                tube.clear(&mut window);
+                tube_basis = Some(basis);
+            }
+            let index = state.current_index.min(state.path.len() - 1);
+            let first = trail.first_keyframe(index).min(index);
+            tube.update(&mut window, &state.path, first, index, |k| {
+                state.keyframe_hue(k)
+            });
+        }
+        if show_shadow {
+            draw_shadow(&mut window, &state, trail);
+        }
+
+        // Mark detected sequences along the path
+        let view: &dyn Camera = if flying { &fly } else { &camera };
+        draw_spans(&mut window, view, &font, &state, &spans);
+        draw_cadences(&mut window, view, &font, &state, &cadences);
+        draw_references(&mut window, view, &font, &references, &overlays, &state);
+        draw_movements(&mut window, view, &font, &state, &movements);
+        if show_labels {
+            draw_labels(
+                &mut window,
+                view,
+                &font,
+                &state,
+                &names,
+                config.label_spacing,
+                config.theme.text,
+            );
+        }
+        let view: &dyn Camera = if flying { &fly } else { &camera };
+        register_pickables(
+            &mut picker,
+            &state,
+            &details,
+            show_markers.then_some(&markers),
+            &voice_positions,
+            show_grid.then(|| axes::origin_radius(view)),
+        );
+        // Bottom overlays stack upwards: progress bar, energy graph, piano
+        // roll, corners
+        let mut lift = 0.0;
+        if show_hud {
+            let index = state.current_index.min(state.path.len() - 1);
+            let readout = format!(
+                "{index} / {}   {} / {}",
+                state.path.len() - 1,
+                hud::clock(state.elapsed()),
+                hud::clock(total_time)
+            );
+            let fraction = state.elapsed() / total_time.max(1e-3);
+            hud::draw_progress(&mut window, &font, fraction, &readout, config.theme.text);
+            lift += hud::PROGRESS_HEIGHT;
+        }
+        if show_energy {
+            draw_energy(&mut window, &state, &energy, peak, lift);
+            lift += ENERGY_HEIGHT + ENERGY_MARGIN;
+        }
+        if show_roll {
+            roll::draw(
+                &mut window,
+                &chords,
+                &state.starts,
+                state.elapsed(),
+                &config.voice_colors,
+                lift,
+            );
+            lift += roll::ROLL_HEIGHT;
+        }
+        if show_axes {
+            axes::draw_axes(
+                &mut window,
+                view,
+                &font,
+                &scene_axes(&axis_names, state.projection),
+            );
+            axes::draw_gizmo(&mut window, view, &font, lift);
+            match state.coloring {
+                Coloring::Motion => axes::draw_legend(
+                    &mut window,
+                    &font,
+                    &axis_names[0],
+                    state.color_scale,
+                    config.palette,
+                    lift,
+                ),
+                Coloring::Quality => {
+                    let families = analysis::FAMILIES
+                        .map(|(name, quality)| (name, config.palette.at(quality.hue())));
+                    axes::draw_key(&mut window, &font, &families, lift);
+                }
+            }
+        }
+        if show_hud {
+            let index = state.current_index.min(chords.len() - 1);
+            let notes = chords[index];
+            let name = names[index].as_deref().unwrap_or("?");
+            let bar = bars.get(index).copied().unwrap_or(1);
+            let beat = beats.get(index).copied().unwrap_or(1.0);
+            let mut lines = vec![
+                format!(
+                    "Keyframe {index} / {}   Bar {bar}:{}",
+                    chords.len() - 1,
+                    format_beat(beat)
+                ),
+                format!("{notes:?}   {name}"),
+                format!(
+                    "{} / {}   {playback_speed}x{}",
+                    hud::clock(state.elapsed()),
+                    hud::clock(total_time),
+                    match (stepping, paused) {
+                        (true, _) => "   stepping",
+                        (false, true) => "   paused",
+                        (false, false) => "",
+                    }
+                ),
+            ];
+            if let Some(object) = picked {
+                lines.push(format!("Picked {}", describe(object, &state, &details)));
+            }
+            hud.draw(&mut window, &font, &lines);
+        }
+        if show_stats {
+            let (mean, worst) = frame_timer.summary();
+            let mut nodes = 0;
+            window.scene().apply_to_scene_nodes(&mut |_| nodes += 1);
+            let index = state.current_index.min(state.path.len() - 1);
+            let last = if trail == Trail::Piece {
+                state.path.len() - 1
+            } else {
+                index
+            };
+            let mut lines = vec![
+                format!("{mean:.1} ms/frame ({:.0} fps)", 1000.0 / mean.max(1e-3)),
+                format!("Slowest frame {worst:.1} ms"),
+                // The root node holds the others and is not counted
+                format!("Scene nodes {}", nodes - 1),
+                format!(
+                    "Trail vertices {}",
+                    (last + 2).saturating_sub(trail.first_keyframe(index))
+                ),
+            ];
+            if show_tube {
+                lines.push(format!("Tube vertices {}", tube.vertex_count()));
+            }
+            lines.push(format!("Keyframe {index} / {}", state.path.len() - 1));
+            hud::draw_stats(
+                &mut window,
+                &font,
+                &lines,
+                config.theme.text,
+                panel.height(),
+            );
+        }
+        panel.draw(&mut window, &font, |control| match control {
+            Control::Speed => (
+                speed_fraction(playback_speed),
+                format!("Speed: {playback_speed}x"),
+            ),
+            Control::Trail => (trail_fraction(trail), format!("Trail: {}", trail.spec())),
+            Control::ColorScale => (
+                log_fraction(state.color_scale, COLOR_SCALE_RANGE),
+                format!("Color scale: {:.3}", state.color_scale),
+            ),
+            Control::Projection => (0.0, format!("Projection: {}", state.projection.name())),
+            Control::Pause => (0.0, if paused { "Resume" } else { "Pause" }.into()),
+            Control::Back => (0.0, "<  Previous keyframe".into()),
+            Control::Forward => (0.0, "Next keyframe  >".into()),
+            Control::Screenshot => (0.0, "Screenshot".into()),
+        });
 
-        // Check for escape key to exit
-        for event in window.events().iter() {
-            if let WindowEvent::Key(key, Action::Release, _) = event.value {
-                if key == Key::Escape {
+        // Panel buttons replay their keyboard shortcuts through the same handlers
+        let width = window.width() as f32;
+        let mut events: VecDeque<WindowEvent> = window.events().iter().map(|e| e.value).collect();
+        let mut slides = Vec::new();
+        while let Some(event) = events.pop_front() {
+            match event {
+                WindowEvent::Key(Key::Escape, Action::Release, _) => {
                     running = false;
                     break;
                 }
+                WindowEvent::Key(Key::Space, Action::Release, _) => {
+                    paused = !paused;
+                    hud.notify(if paused { "Paused" } else { "Resumed" }.into());
+                }
+                // Seek by keyframe (arrows, unless flying) or by bar (page keys)
+                WindowEvent::Key(Key::Right, Action::Press, _) if !flying => {
+                    state.seek(state.current_index + 1);
+                }
+                WindowEvent::Key(Key::Left, Action::Press, _) if !flying => {
+                    state.seek(state.current_index.saturating_sub(1));
+                }
+                WindowEvent::Key(Key::PageDown, Action::Press, _) => {
+                    state.seek(next_bar(state.current_index));
+                }
+                WindowEvent::Key(Key::PageUp, Action::Press, _) => {
+                    // Back to the start of this bar, or of the previous one if already there
+                    let start = bar_start(state.current_index);
+                    let target = if start == state.current_index {
+                        bar_start(start.saturating_sub(1))
+                    } else {
+                        start
+                    };
+                    state.seek(target);
+                }
+                WindowEvent::Key(Key::Equals | Key::Add, Action::Press, _) => {
+                    playback_speed = SPEED_LEVELS
+                        .into_iter()
+                        .find(|&level| level > playback_speed)
+                        .unwrap_or(playback_speed);
+                    window.set_title(&title(playback_speed));
+                    hud.notify(format!("Speed: {playback_speed}x"));
+                }
+                WindowEvent::Key(Key::Minus | Key::Subtract, Action::Press, _) => {
+                    playback_speed = SPEED_LEVELS
+                        .into_iter()
+                        .rev()
+                        .find(|&level| level < playback_speed)
+                        .unwrap_or(playback_speed);
+                    window.set_title(&title(playback_speed));
+                    hud.notify(format!("Speed: {playback_speed}x"));
+                }
+                WindowEvent::Key(Key::F, Action::Release, _) => {
+                    flying = !flying;
+                    if flying {
+                        // Take off from wherever the orbit camera is looking
+                        fly.look_at(camera.eye(), camera.at());
+                        hud.notify("Camera: free-fly (arrows move, drag to look)".into());
+                    } else {
+                        hud.notify("Camera: orbit".into());
+                    }
+                }
+                WindowEvent::Key(
+                    key @ (Key::Key1 | Key::Key2 | Key::Key3 | Key::Key4 | Key::Key5 | Key::Key6),
+                    Action::Press,
+                    _,
+                ) => {
+                    let number = key as u8 - Key::Key1 as u8 + 1;
+                    if let Some(view) = camera::preset(number, View::of(&camera), &state.path, home)
+                    {
+                        flying = false;
+                        glide = Some(Glide::new(View::of(&camera), view));
+                    }
+                }
+                WindowEvent::Key(Key::R, Action::Release, modifiers)
+                    if modifiers.contains(Modifiers::Shift) =>
+                {
+                    match camera_take.take() {
+                        Some(take) => save_camera_path(&take, &config.path, &mut hud),
+                        None => {
+                            camera_take = Some(CameraPath::default());
+                            hud.notify("Recording camera (Shift+R to stop)".to_string());
+                        }
+                    }
+                }
+                WindowEvent::Key(Key::R, Action::Release, _) => {
+                    state.restart();
+                    particles = Particles::default();
+                    step_to = None;
+                    looped = false;
+                    hud.notify("Restarted from the first keyframe".into());
+                }
+                WindowEvent::Key(Key::S, Action::Release, _) => {
+                    let path = output_path(&config.path, "png");
+                    match window.snap_image().save(&path) {
+                        Ok(()) => hud.notify(format!("Saved screenshot to {}", path.display())),
+                        Err(e) => eprintln!("[-.-] Could not save screenshot: {e}"),
+                    }
+                }
+                WindowEvent::Key(Key::I, Action::Release, _) => {
+                    isocontours.set_visible(!isocontours.visible());
+                }
+                WindowEvent::Key(Key::E, Action::Release, _) => show_energy = !show_energy,
+                WindowEvent::Key(Key::A, Action::Release, _) => show_axes = !show_axes,
+                WindowEvent::Key(Key::D, Action::Release, _) => show_shadow = !show_shadow,
+                WindowEvent::Key(Key::M, Action::Release, _) => show_roll = !show_roll,
+                WindowEvent::Key(Key::U, Action::Release, _) => {
+                    show_tube = !show_tube;
+                    tube.set_visible(show_tube);
+                }
+                WindowEvent::Key(Key::K, Action::Release, _) => {
+                    looping = !looping;
+                    hud.notify(format!("Loop: {}", if looping { "on" } else { "off" }));
+                }
+                WindowEvent::Key(Key::L, Action::Release, _) => show_labels = !show_labels,
+                WindowEvent::Key(Key::H, Action::Release, _) => show_hud = !show_hud,
+                WindowEvent::Key(Key::F3, Action::Release, _) => show_stats = !show_stats,
+                WindowEvent::Key(Key::F2, Action::Release, _) => show_ghost = !show_ghost,
+                WindowEvent::Key(Key::Tab, Action::Release, _) => panel.visible = !panel.visible,
+                WindowEvent::Key(Key::J, Action::Release, _) => show_downbeats = !show_downbeats,
+                WindowEvent::Key(Key::Z, Action::Release, _) => show_markers = !show_markers,
+                WindowEvent::Key(Key::X, Action::Release, _) => {
+                    slice = match slice {
+                        Some(_) => None,
+                        None => {
+                            if state.projection != Projection::DropW {
+                                state.projection = Projection::DropW;
+                                state.reproject();
+                            }
+                            Some(middle_w(&state))
+                        }
+                    };
+                    hud.notify(match slice {
+                        Some(level) => format!("Slice at w = {level:.1} (drop-w)"),
+                        None => "Slice off".to_string(),
+                    });
+                }
+                WindowEvent::Key(key @ (Key::Comma | Key::Period), Action::Press, _)
+                    if slice.is_some() =>
+                {
+                    let (low, high) = state.w_extent();
+                    let step = (high - low) * SLICE_STEP;
+                    let level =
+                        slice.unwrap_or_default() + if key == Key::Period { step } else { -step };
+                    slice = Some(level.clamp(low, high));
+                    hud.notify(format!("Slice at w = {:.1}", level.clamp(low, high)));
+                }
+                WindowEvent::Key(Key::C, Action::Release, _) => {
+                    state.set_coloring(state.coloring.next(), &chords);
+                    trail_colors = TrailColors::new(&state, &step_sizes, config.palette);
+                    hud.notify(format!("Coloring: {}", state.coloring.name()));
+                }
+                WindowEvent::Key(Key::G, Action::Release, _) => {
+                    glyph_mode = glyph_mode.next();
+                    sphere.set_visible(glyph_mode.shows_sphere());
+                    tetrahedron.set_visible(glyph_mode.shows_tetrahedron());
+                    hud.notify(format!("Glyph: {}", glyph_mode.name()));
+                }
+                WindowEvent::Key(Key::Q, Action::Release, _) => show_grid = !show_grid,
+                WindowEvent::Key(key @ (Key::F7 | Key::F8), Action::Release, _) => {
+                    grid_cell = if key == Key::F7 {
+                        grid_cell / 2.0
+                    } else {
+                        grid_cell * 2.0
+                    };
+                    hud.notify(format!("Grid cell: {grid_cell} units"));
+                }
+                WindowEvent::Key(key @ (Key::F9 | Key::F10), Action::Release, _) => {
+                    grid_cells = if key == Key::F9 {
+                        grid_cells.saturating_sub(1).max(1)
+                    } else {
+                        (grid_cells + 1).min(GRID_MAX_CELLS)
+                    };
+                    hud.notify(format!("Grid extent: {grid_cells} cells"));
+                }
+                WindowEvent::Key(Key::B, Action::Release, _) => show_bloom = !show_bloom,
+                WindowEvent::Key(Key::Y, Action::Release, _) => show_anaglyph = !show_anaglyph,
+                WindowEvent::Key(Key::F4, Action::Release, _) => {
+                    framing = !framing;
+                    hud.notify(format!(
+                        "Auto framing: {}",
+                        if framing { "on" } else { "off" }
+                    ));
+                }
+                WindowEvent::Key(Key::F5, Action::Release, _) => wireframe = !wireframe,
+                WindowEvent::Key(Key::W, Action::Release, _) => {
+                    orbiting = !orbiting;
+                    hud.notify(format!("Orbit: {}", if orbiting { "on" } else { "off" }));
+                }
+                WindowEvent::Key(Key::V, Action::Release, _) => {
+                    show_voices = !show_voices;
+                    for node in &mut voice_spheres {
+                        node.set_visible(show_voices);
+                    }
+                }
+                WindowEvent::Key(Key::O, Action::Release, _) => {
+                    show_orientation = !show_orientation;
+                }
+                WindowEvent::Key(Key::LBracket, Action::Release, _) => {
+                    rotation_speed -= ROTATION_STEP;
+                    hud.notify(format!("4D rotation speed: {rotation_speed:.2} rad/s"));
+                }
+                WindowEvent::Key(Key::RBracket, Action::Release, _) => {
+                    rotation_speed += ROTATION_STEP;
+                    hud.notify(format!("4D rotation speed: {rotation_speed:.2} rad/s"));
+                }
+                WindowEvent::Key(Key::Key0, Action::Release, _) => {
+                    rotation_speed = 0.0;
+                    state.rotation = 0.0;
+                    state.reproject();
+                }
+                WindowEvent::Key(Key::T, Action::Release, _) => {
+                    stepping = !stepping;
+                    step_to = None;
+                    hud.notify(if stepping {
+                        "Step mode: N next keyframe, P previous".into()
+                    } else {
+                        "Step mode off".into()
+                    });
+                }
+                WindowEvent::Key(Key::N, Action::Press, _) if stepping => {
+                    let from = step_to.unwrap_or(0).max(state.current_index);
+                    step_to = Some((from + 1).min(state.motions.len()));
+                }
+                WindowEvent::Key(Key::P, Action::Press, _) if stepping => {
+                    step_to = None;
+                    state.seek(state.current_index.saturating_sub(1));
+                }
+                WindowEvent::Key(Key::P, Action::Release, _) if !stepping => {
+                    cycle_projection(&mut state, &mut hud);
+                }
+                WindowEvent::CursorPos(x, y, _) => {
+                    cursor = (x, y);
+                    slides.extend(panel.drag(cursor, width));
+                }
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _)
+                    if panel.contains(cursor, width) =>
+                {
+                    // Keep the camera still while the panel has the mouse
+                    on_panel = true;
+                    camera.rebind_rotate_button(None);
+                    fly.rebind_rotate_button(None);
+                    match panel.press(cursor, width) {
+                        // P steps back in step mode, so the button cycles directly
+                        Some(PanelEvent::Click(Control::Projection)) => {
+                            cycle_projection(&mut state, &mut hud);
+                        }
+                        Some(PanelEvent::Click(control)) => events.extend(control.shortcut()),
+                        slide => slides.extend(slide),
+                    }
+                }
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _)
+                    if show_hud && hud::progress_at(&window, cursor).is_some() =>
+                {
+                    // Seek to the motion playing at that point of the piece,
+                    // holding the camera still like the panel does
+                    on_panel = true;
+                    camera.rebind_rotate_button(None);
+                    fly.rebind_rotate_button(None);
+                    let fraction = hud::progress_at(&window, cursor).unwrap_or(0.0);
+                    let time = fraction * total_time;
+                    let index = state.starts.partition_point(|&start| start <= time);
+                    state.seek(index.saturating_sub(1));
+                }
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                    // Grabbing the camera takes over from a running preset move
+                    glide = None;
+                    press_at = Some(cursor);
+                }
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) if on_panel => {
+                    on_panel = false;
+                    panel.release();
+                    camera.rebind_rotate_button(Some(MouseButton::Button1));
+                    fly.rebind_rotate_button(Some(MouseButton::Button1));
+                }
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) => {
+                    // A press and release without dragging the camera is a click
+                    if let Some((px, py)) = press_at.take()
+                        && (cursor.0 - px).hypot(cursor.1 - py) <= CLICK_TOLERANCE
+                    {
+                        let size = (window.width(), window.height());
+                        let at = (cursor.0.max(0.0) as u32, cursor.1.max(0.0) as u32);
+                        let view: &dyn Camera = if flying { &fly } else { &camera };
+                        picked = picker.pick(view, at, size);
+                        if let Some(object) = picked {
+                            println!("[^.^] Picked {}", describe(object, &state, &details));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for slide in slides {
+            match slide {
+                PanelEvent::Slide(Control::Speed, fraction) => {
+                    playback_speed = speed_at(fraction);
+                    window.set_title(&title(playback_speed));
+                }
+                PanelEvent::Slide(Control::Trail, fraction) => trail = trail_at(fraction),
+                PanelEvent::Slide(Control::ColorScale, fraction) => {
+                    state.set_color_scale(log_value(fraction, COLOR_SCALE_RANGE));
+                    trail_colors = TrailColors::new(&state, &step_sizes, config.palette);
+                }
+                _ => {}
             }
         }
     }
+
+    if let Some(take) = camera_take {
+        save_camera_path(&take, &config.path, &mut hud);
+    }
+    if let Some(recorder) = recorder {
+        match recorder.finish() {
+            Ok(summary) => println!("[^.^] Recorded {summary}"),
+            Err(e) => eprintln!("[-.-] Recording failed: {e}"),
+        }
+    }
 }
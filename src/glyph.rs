@@ -0,0 +1,93 @@
+use kiss3d::nalgebra::{Point3, Vector3};
+use kiss3d::resource::Mesh;
+use kiss3d::scene::SceneNode;
+use kiss3d::window::Window;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const TETRA_RADIUS: f32 = 45.0; // Horizontal reach of each voice vertex, clear of the sphere
+const TETRA_HEIGHT: f32 = 4.0; // Height per semitone away from the chord's mean pitch
+
+// What marks the current chord
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Glyph {
+    Sphere,      // The single colored sphere
+    Tetrahedron, // One vertex per voice, deformed by the voicing
+    Both,        // Tetrahedron drawn around the sphere
+}
+
+impl Glyph {
+    pub fn from_name(name: &str) -> Option<Glyph> {
+        match name {
+            "sphere" => Some(Glyph::Sphere),
+            "tetrahedron" => Some(Glyph::Tetrahedron),
+            "both" => Some(Glyph::Both),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Glyph::Sphere => "sphere",
+            Glyph::Tetrahedron => "tetrahedron",
+            Glyph::Both => "both",
+        }
+    }
+
+    // Next glyph when cycling at runtime
+    pub fn next(self) -> Glyph {
+        match self {
+            Glyph::Sphere => Glyph::Tetrahedron,
+            Glyph::Tetrahedron => Glyph::Both,
+            Glyph::Both => Glyph::Sphere,
+        }
+    }
+
+    pub fn shows_sphere(self) -> bool {
+        self != Glyph::Tetrahedron
+    }
+
+    pub fn shows_tetrahedron(self) -> bool {
+        self != Glyph::Sphere
+    }
+}
+
+// Add a tetrahedron whose vertices are rewritten every frame
+pub fn add_tetrahedron(window: &mut Window) -> SceneNode {
+    let coords = shape([0.0, 1.0, 2.0, 3.0]).to_vec();
+    let faces = vec![
+        Point3::new(0, 1, 2),
+        Point3::new(0, 3, 1),
+        Point3::new(0, 2, 3),
+        Point3::new(1, 3, 2),
+    ];
+    let mesh = Mesh::new(coords, faces, None, None, true);
+    let mut node = window.add_mesh(Rc::new(RefCell::new(mesh)), Vector3::new(1.0, 1.0, 1.0));
+    // Voice crossings turn the faces inside out, so draw both sides
+    node.enable_backface_culling(false);
+    node
+}
+
+// Move the tetrahedron's vertices to the given voice pitches, returning
+// them relative to the glyph's center
+pub fn update_tetrahedron(node: &mut SceneNode, pitches: [f32; 4]) -> [Point3<f32>; 4] {
+    let vertices = shape(pitches);
+    node.modify_vertices(&mut |coords| coords.copy_from_slice(&vertices));
+    node.recompute_normals();
+    vertices
+}
+
+// Each voice keeps its own compass direction around the center, and rises
+// or falls with its pitch relative to the others: spread stretches the
+// glyph vertically and inversions change which corner sits lowest
+fn shape(pitches: [f32; 4]) -> [Point3<f32>; 4] {
+    let mean = pitches.iter().sum::<f32>() / 4.0;
+    std::array::from_fn(|v| {
+        let angle = v as f32 * std::f32::consts::FRAC_PI_2;
+        Point3::new(
+            TETRA_RADIUS * angle.cos(),
+            (pitches[v] - mean) * TETRA_HEIGHT,
+            TETRA_RADIUS * angle.sin(),
+        )
+    })
+}
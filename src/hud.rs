@@ -0,0 +1,152 @@
+use crate::rgba::Rgb;
+use kiss3d::nalgebra::{Point2, Point3};
+use kiss3d::text::Font;
+use kiss3d::window::Window;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+const HUD_SIZE: f32 = 30.0; // Font size of the overlay text
+const HUD_MARGIN: f32 = 12.0; // Distance from the window's top-left corner
+const LINE_HEIGHT: f32 = 34.0;
+const NOTICE_TIME: Duration = Duration::from_secs(3); // How long a notice stays up
+const NOTICE_COLOR: (f32, f32, f32) = (1.0, 0.85, 0.4);
+const PROGRESS_MARGIN: f32 = 12.0; // Distance of the progress bar from the window edges
+const PROGRESS_THICKNESS: f32 = 6.0;
+const PROGRESS_TEXT: f32 = 22.0; // Font size of the readout above the bar
+pub const PROGRESS_HEIGHT: f32 = 2.0 * PROGRESS_MARGIN + PROGRESS_THICKNESS + PROGRESS_TEXT;
+const STATS_FRAMES: usize = 120; // Frames the timing readout covers
+const STATS_WIDTH: f32 = 420.0; // Room left for the stats block at the right edge
+
+// 2D playback overlay: a block of readout lines plus the latest notice
+pub struct Hud {
+    color: Rgb,                        // Readout text color
+    notice: Option<(String, Instant)>, // Last message and when it was posted
+}
+
+impl Hud {
+    pub fn new(color: Rgb) -> Hud {
+        Hud {
+            color,
+            notice: None,
+        }
+    }
+
+    // Show a message on screen for a few seconds, echoing it to the console
+    pub fn notify(&mut self, message: String) {
+        println!("[^.^] {message}");
+        self.notice = Some((message, Instant::now()));
+    }
+
+    pub fn draw(&self, window: &mut Window, font: &Rc<Font>, lines: &[String]) {
+        let (r, g, b) = self.color;
+        let color = Point3::new(r, g, b);
+        for (i, line) in lines.iter().enumerate() {
+            let at = Point2::new(HUD_MARGIN, HUD_MARGIN + i as f32 * LINE_HEIGHT);
+            window.draw_text(line, &at, HUD_SIZE, font, &color);
+        }
+
+        if let Some((message, posted)) = &self.notice
+            && posted.elapsed() < NOTICE_TIME
+        {
+            let (r, g, b) = NOTICE_COLOR;
+            let at = Point2::new(
+                HUD_MARGIN,
+                HUD_MARGIN + (lines.len() as f32 + 0.5) * LINE_HEIGHT,
+            );
+            window.draw_text(message, &at, HUD_SIZE, font, &Point3::new(r, g, b));
+        }
+    }
+}
+
+// Wall-clock length of the last few frames, for the stats overlay
+pub struct FrameTimer {
+    frames: VecDeque<f32>, // Seconds per frame, oldest first
+}
+
+impl FrameTimer {
+    pub fn new() -> FrameTimer {
+        FrameTimer {
+            frames: VecDeque::with_capacity(STATS_FRAMES),
+        }
+    }
+
+    pub fn record(&mut self, seconds: f32) {
+        if self.frames.len() == STATS_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(seconds);
+    }
+
+    // Mean and longest frame time in milliseconds
+    pub fn summary(&self) -> (f32, f32) {
+        let count = self.frames.len().max(1) as f32;
+        let mean = self.frames.iter().sum::<f32>() / count;
+        let worst = self.frames.iter().copied().fold(0.0, f32::max);
+        (mean * 1000.0, worst * 1000.0)
+    }
+}
+
+// Diagnostic readout block at the right edge, `top` pixels down from the
+// top so it can clear the control panel
+pub fn draw_stats(
+    window: &mut Window,
+    font: &Rc<Font>,
+    lines: &[String],
+    (r, g, b): Rgb,
+    top: f32,
+) {
+    let left = window.width() as f32 - STATS_WIDTH;
+    let color = Point3::new(r, g, b);
+    for (i, line) in lines.iter().enumerate() {
+        let at = Point2::new(left, top + HUD_MARGIN + i as f32 * LINE_HEIGHT);
+        window.draw_text(line, &at, HUD_SIZE, font, &color);
+    }
+}
+
+// Thin bar along the bottom edge filled to `fraction` of the piece, with
+// `readout` above its left end
+pub fn draw_progress(
+    window: &mut Window,
+    font: &Rc<Font>,
+    fraction: f32,
+    readout: &str,
+    (r, g, b): Rgb,
+) {
+    let (width, height) = (window.width() as f32, window.height() as f32);
+    let (left, right) = (PROGRESS_MARGIN, width - PROGRESS_MARGIN);
+    let split = left + (right - left) * fraction.clamp(0.0, 1.0);
+    let bottom = height - PROGRESS_MARGIN;
+    let done = Point3::new(r, g, b);
+    let rest = Point3::new(r * 0.3, g * 0.3, b * 0.3);
+    for row in 0..PROGRESS_THICKNESS as usize {
+        let y = bottom - row as f32;
+        window.draw_planar_line(&planar(window, left, y), &planar(window, split, y), &done);
+        window.draw_planar_line(&planar(window, split, y), &planar(window, right, y), &rest);
+    }
+    let at = Point2::new(left, bottom - PROGRESS_THICKNESS - PROGRESS_TEXT - 4.0);
+    window.draw_text(readout, &at, PROGRESS_TEXT, font, &done);
+}
+
+// Share of the piece under the cursor when it is on the progress bar
+pub fn progress_at(window: &Window, (x, y): (f64, f64)) -> Option<f32> {
+    let (width, height) = (window.width() as f32, window.height() as f32);
+    let (x, y) = (x as f32, y as f32);
+    let span = width - 2.0 * PROGRESS_MARGIN;
+    let on_bar = y >= height - PROGRESS_HEIGHT && span > 0.0;
+    on_bar.then(|| ((x - PROGRESS_MARGIN) / span).clamp(0.0, 1.0))
+}
+
+// Planar lines use logical units centred on the window, y up; overlays
+// are laid out in pixels from the top left like text, and converted here
+pub fn planar(window: &Window, x: f32, y: f32) -> Point2<f32> {
+    let (width, height) = (window.width() as f32, window.height() as f32);
+    let scale = window.scale_factor() as f32;
+    Point2::new((x - width / 2.0) / scale, (height / 2.0 - y) / scale)
+}
+
+// Minutes and seconds, e.g. 1:05.3
+pub fn clock(seconds: f32) -> String {
+    let seconds = seconds.max(0.0);
+    format!("{}:{:04.1}", (seconds / 60.0) as u32, seconds % 60.0)
+}
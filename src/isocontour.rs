@@ -0,0 +1,129 @@
+use crate::mapping::ChordSpaceMapping;
+use crate::shading::Finish;
+use crate::transformation::{self, Chord};
+use kiss3d::nalgebra::{Point3, Vector3};
+use kiss3d::ncollide3d::procedural;
+use kiss3d::scene::SceneNode;
+use kiss3d::window::Window;
+
+const DISTANCES: [i32; 3] = [1, 2, 3]; // Semitones of total voice-leading work
+const SHELL_OPACITY: f32 = 0.3; // Opacity of the nearest shell, fading with distance
+const SHELL_COLOR: (f32, f32, f32) = (0.3, 0.8, 0.9);
+const SUBDIVISIONS: (u32, u32) = (32, 16); // Shell mesh resolution around and top to bottom
+
+// 4D locations of the chords each isocontour distance away from `chord`,
+// found by mapping every voice leading of that size the way the piece is
+// mapped and adding it to `at`, the 4D point of `chord` on the path
+pub fn neighbours(
+    mapper: &dyn ChordSpaceMapping,
+    chord: Chord,
+    at: [f32; 4],
+) -> Vec<Vec<[f32; 4]>> {
+    DISTANCES
+        .iter()
+        .map(|&distance| {
+            transformation::voice_steps(distance)
+                .iter()
+                .filter_map(|step| {
+                    let next = [0, 1, 2, 3].map(|v| chord[v] + step[v]);
+                    let motion = *mapper.map(&[chord, next]).first()?;
+                    Some([0, 1, 2, 3].map(|j| at[j] + motion[j]))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Translucent shells around the current chord, one per isocontour
+// distance, each wrapped around the chords that far away as they appear
+// in the scene. A shell's radius in every direction is how far its
+// farthest chord reaches that way, so each one encloses its chords and
+// touches the outermost; the chords themselves are drawn as points.
+pub struct Isocontours {
+    shells: Vec<SceneNode>,        // One mesh per distance, nearest first
+    directions: Vec<Vector3<f32>>, // Unit direction of every shell vertex
+    points: Vec<Vec<Point3<f32>>>, // Neighbouring chords per distance, in the scene
+    visible: bool,
+}
+
+impl Isocontours {
+    pub fn new(window: &mut Window, visible: bool) -> Isocontours {
+        let (around, down) = SUBDIVISIONS;
+        let sphere = procedural::unit_sphere(around, down, false);
+        let directions = sphere
+            .coords
+            .iter()
+            .map(|p| p.coords.try_normalize(1e-6).unwrap_or_else(Vector3::y))
+            .collect();
+        let (r, g, b) = SHELL_COLOR;
+        let shells = DISTANCES
+            .iter()
+            .map(|&distance| {
+                let mut node = window.add_trimesh(sphere.clone(), Vector3::new(1.0, 1.0, 1.0));
+                let fade = 1.0 / distance as f32;
+                node.set_color(r, g, b);
+                node.enable_backface_culling(false);
+                node.data_mut()
+                    .get_object_mut()
+                    .set_user_data(Box::new(Finish {
+                        emissive: 1.0,
+                        opacity: SHELL_OPACITY * fade,
+                    }));
+                node.set_visible(false);
+                node
+            })
+            .collect();
+        Isocontours {
+            shells,
+            directions,
+            points: Vec::new(),
+            visible,
+        }
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+        for (shell, points) in self.shells.iter_mut().zip(&self.points) {
+            shell.set_visible(visible && !points.is_empty());
+        }
+    }
+
+    // Wrap the shells around `center` through the neighbouring chords of
+    // each distance, already placed in the scene
+    pub fn update(&mut self, center: Point3<f32>, points: Vec<Vec<Point3<f32>>>) {
+        for (shell, chords) in self.shells.iter_mut().zip(&points) {
+            let directions = &self.directions;
+            shell.modify_vertices(&mut |coords| {
+                for (vertex, direction) in coords.iter_mut().zip(directions) {
+                    let reach = chords
+                        .iter()
+                        .map(|p| (p - center).dot(direction))
+                        .fold(0.0, f32::max);
+                    *vertex = center + direction * reach;
+                }
+            });
+            shell.recompute_normals();
+        }
+        self.points = points;
+        self.set_visible(self.visible);
+    }
+
+    // The neighbouring chords, fading with distance
+    pub fn draw(&self, window: &mut Window) {
+        if !self.visible {
+            return;
+        }
+        let (r, g, b) = SHELL_COLOR;
+        for (distance, points) in DISTANCES.iter().zip(&self.points) {
+            let fade = 1.0 / *distance as f32;
+            let color = Point3::new(r * fade, g * fade, b * fade);
+            for point in points {
+                window.draw_point(point, &color);
+            }
+        }
+    }
+}
@@ -1,51 +1,364 @@
+mod analysis;
+mod axes;
+mod bloom;
+mod camera;
+mod capture;
+mod config;
+mod cues;
+mod easing;
 mod engine;
+mod glyph;
+mod hud;
+mod isocontour;
+mod json;
+mod mapping;
+mod mesh;
 mod midi;
+mod panel;
+mod picking;
+mod projection;
+mod reference;
 mod rgba;
+mod roll;
+mod shading;
+mod sky;
+mod smoothing;
+mod sound;
+mod stereo;
+mod theme;
+mod trail;
 mod transformation;
+mod tube;
+mod workspace;
 
 use std::env;
+use transformation::{Metric, MotionType};
 //use std::ffi::OsStr;
-use std::path::Path;
 use std::process;
 
+// Print a motion vector compactly: whole numbers without decimals
+fn format_vector(v: &[f32; 4]) -> String {
+    let parts: Vec<String> = v
+        .iter()
+        .map(|x| {
+            let text = format!("{x:.2}");
+            match text.trim_end_matches('0').trim_end_matches('.') {
+                "-0" => "0".to_string(),
+                trimmed => trimmed.to_string(),
+            }
+        })
+        .collect();
+    format!("[{}]", parts.join(", "))
+}
+
+// Keyframe path of another piece, parsed and mapped like the main one
+fn overlay(
+    path: &std::path::Path,
+    config: &config::Config,
+    mapper: &dyn mapping::ChordSpaceMapping,
+) -> Result<reference::OverlayPath, Box<dyn std::error::Error>> {
+    let movements = workspace::movements(path)?;
+    let (keyframes, _) =
+        workspace::open(&movements, config.sequence, config.fill, config.keyframes)?;
+    let mut motions = mapper.map(&keyframes.chords);
+    if config.per_beat {
+        motions = transformation::per_beat(&motions, &keyframes.durations());
+    }
+    let origin = mapper.origin(&keyframes.chords);
+    let label = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok(reference::OverlayPath {
+        label,
+        origin,
+        motions,
+    })
+}
+
 fn main() {
     // parse args
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("[-.-] Usage: ./visual <path-to-midi-file>");
-        process::exit(1);
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // diagnostics subcommand
+    if args.first().map(String::as_str) == Some("inspect") {
+        let Some(file) = args.get(1) else {
+            eprintln!("[-.-] Usage: ./visual inspect <path-to-midi-file>");
+            process::exit(1);
+        };
+        if let Err(e) = midi::inspect(std::path::Path::new(file)) {
+            eprintln!("[-.-] Could not inspect {:?}: {e}", file);
+            process::exit(1);
+        }
+        return;
     }
 
+    let config = match config::parse_args(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("[-.-] {e}");
+            eprintln!("[-.-] {}", config::USAGE);
+            process::exit(1);
+        }
+    };
+
     // resolve path
-    let path = Path::new(&args[1]);
+    let path = config.path.as_path();
     if !path.exists() {
         eprintln!("[-.-] Path: {:?} does not exist", path);
     } else {
-        println!("[^.^] Found midi file at {:?}", path);
+        println!("[^.^] Found input at {:?}", path);
     }
 
-    // parse midi file
-    let voice_leadings: Vec<[i32; 4]> = midi::parse(path).expect("REASON");
+    // parse midi file(s)
+    let movements = match workspace::movements(path) {
+        Ok(movements) => movements,
+        Err(e) => {
+            eprintln!("[-.-] Could not read {:?}: {e}", path);
+            process::exit(1);
+        }
+    };
+    let (keyframes, movement_spans) =
+        match workspace::open(&movements, config.sequence, config.fill, config.keyframes) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("[-.-] Could not parse {:?}: {e}", path);
+                process::exit(1);
+            }
+        };
+    if movements.len() > 1 {
+        println!("[^.^] Loaded {} movements", movements.len());
+    }
 
+    let voice_leadings = &keyframes.chords;
     println!("🎵 Parsed Voice Leadings:");
     for (i, chord) in voice_leadings.iter().enumerate() {
         println!("{:03}: {:?}", i, chord);
     }
 
+    // detect sequences and interval cycles
+    let changes = analysis::chord_changes(voice_leadings);
+    let sequences = analysis::detect_sequences(&changes);
+    println!("\n🔁 Sequences and Interval Cycles:");
+    for span in &sequences {
+        println!("{:03}-{:03}: {}", span.first, span.last, span.label);
+    }
+    let cadences =
+        analysis::detect_cadences(voice_leadings, &keyframes.durations(), &changes, &sequences);
+    println!("\n🏁 Cadences:");
+    for cadence in &cadences {
+        println!(
+            "{:03}: {} {} -> {}",
+            cadence.frame,
+            cadence.kind.label(),
+            cadence.from,
+            cadence.to
+        );
+    }
+    // classify voice-pair motion for every step
+    let motion_types = transformation::classify(voice_leadings);
+    let mut counts = [0; 5];
+    for types in &motion_types {
+        for &t in types {
+            counts[t as usize] += 1;
+        }
+    }
+    println!(
+        "\n🎼 Voice-pair motion: {} parallel, {} similar, {} oblique, {} contrary",
+        counts[MotionType::Parallel as usize],
+        counts[MotionType::Similar as usize],
+        counts[MotionType::Oblique as usize],
+        counts[MotionType::Contrary as usize]
+    );
+
+    if let Some(report_path) = &config.analysis {
+        match analysis::export(report_path, &changes, &sequences, &cadences, &motion_types) {
+            Ok(()) => println!("[^.^] Wrote analysis report to {:?}", report_path),
+            Err(e) => eprintln!("[-.-] Could not write analysis report: {e}"),
+        }
+    }
+
+    // voice-leading sizes
+    println!("\n📏 Voice-leading size per step:");
+    for metric in Metric::ALL {
+        let sizes = transformation::voice_leading_sizes(voice_leadings, metric);
+        let moving: Vec<f32> = sizes.iter().copied().filter(|&s| s > 0.0).collect();
+        let mean = moving.iter().sum::<f32>() / moving.len().max(1) as f32;
+        let max = moving.iter().copied().fold(0.0, f32::max);
+        println!(
+            "{:>2}: mean {mean:.2}, max {max:.2} over {} moving step(s)",
+            metric.name(),
+            moving.len()
+        );
+    }
+    let step_sizes = config.trail_metric.map_or(Vec::new(), |metric| {
+        transformation::voice_leading_sizes(voice_leadings, metric)
+    });
+
+    // explain a non-default basis
+    if config.matrix != transformation::DEFAULT_BASIS
+        && let Ok(diagnostics) = transformation::diagnose(config.matrix)
+    {
+        println!("\n📐 Basis in use:");
+        for line in transformation::basis_report(config.matrix, &diagnostics) {
+            println!("{line}");
+        }
+    }
+
     // transform sequence
-    let transformation: Vec<[i32; 4]> = transformation::convert(voice_leadings);
-    let mut total_shift = [0; 4];
+    let mapper = mapping::create(&config.mapping, &config).expect("mapping checked by config");
+    let mut transformation = mapper.map(voice_leadings);
+    if config.per_beat {
+        transformation = transformation::per_beat(&transformation, &keyframes.durations());
+    }
+    let origin = mapper.origin(voice_leadings);
+    let mut total_shift = [0.0; 4];
     println!("\n🎹 Transformed Voice Motion Vectors:");
     for (i, vec) in transformation.iter().enumerate() {
-        println!("{:03}: {:?}", i, vec);
+        println!("{:03}: {}", i, format_vector(vec));
         for j in 0..4 {
             total_shift[j] += vec[j];
         }
     }
-    println!("\n🧮 Total shift [total, x, y, z]: {:?}", total_shift);
+    println!(
+        "\n🧮 Total shift [total, x, y, z]: {}",
+        format_vector(&total_shift)
+    );
+
+    // per-movement statistics
+    if movement_spans.len() > 1 {
+        let durations = keyframes.durations();
+        println!("\n📚 Movements:");
+        for span in &movement_spans {
+            // Motions inside the movement (the step into it belongs to the boundary)
+            let range = span.first..span.last.min(transformation.len());
+            let mut shift = [0.0; 4];
+            let mut travelled = 0.0;
+            for motion in &transformation[range.clone()] {
+                for j in 0..4 {
+                    shift[j] += motion[j];
+                }
+                travelled +=
+                    (motion[1] * motion[1] + motion[2] * motion[2] + motion[3] * motion[3]).sqrt();
+            }
+            let beats: f32 = durations[range].iter().sum::<f32>() / 4.0;
+            let sequences = sequences
+                .iter()
+                .filter(|s| s.first >= span.first && s.last <= span.last)
+                .count();
+            println!(
+                "{}: keyframes {:03}-{:03}, {beats} beats, travelled {travelled:.1}, shift {}, {sequences} sequence(s)",
+                span.label,
+                span.first,
+                span.last,
+                format_vector(&shift)
+            );
+        }
+    }
+
+    // export this piece's path for use as a reference elsewhere
+    if let Some(export_path) = &config.export_path {
+        let label = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let points = reference::keyframe_points(origin, &transformation);
+        match reference::export(export_path, &label, &points) {
+            Ok(()) => println!("[^.^] Wrote keyframe path to {:?}", export_path),
+            Err(e) => eprintln!("[-.-] Could not write keyframe path: {e}"),
+        }
+    }
+
+    // export the trajectory as it appears in the scene, for Blender or printing
+    if let Some(mesh_path) = &config.export_mesh {
+        if transformation.is_empty() {
+            eprintln!("[-.-] A single keyframe has no trajectory, skipping the mesh");
+        } else {
+            let (points, colors) =
+                engine::trajectory(origin, &transformation, &keyframes.chords, &config);
+            match mesh::export(mesh_path, &points, &colors, config.mesh_radius) {
+                Ok(()) => println!("[^.^] Wrote trajectory mesh to {:?}", mesh_path),
+                Err(e) => eprintln!("[-.-] Could not write trajectory mesh: {e}"),
+            }
+        }
+    }
+
+    // load reference paths
+    let mut references = Vec::new();
+    for reference_path in &config.references {
+        match reference::load(reference_path) {
+            Ok(reference) => {
+                println!(
+                    "[^.^] Loaded reference \"{}\" ({} points)",
+                    reference.label,
+                    reference.points.len()
+                );
+                references.push(reference);
+            }
+            Err(e) => {
+                eprintln!("[-.-] Could not load reference {:?}: {e}", reference_path);
+                process::exit(1);
+            }
+        }
+    }
+    // whole paths of other pieces, mapped the same way as this one
+    let mut overlays = Vec::new();
+    for overlay_path in &config.overlay {
+        match overlay(overlay_path, &config, mapper.as_ref()) {
+            Ok(overlay) => {
+                println!(
+                    "[^.^] Overlaying \"{}\" ({} keyframes)",
+                    overlay.label,
+                    overlay.motions.len() + 1
+                );
+                overlays.push(overlay);
+            }
+            Err(e) => {
+                eprintln!("[-.-] Could not overlay {:?}: {e}", overlay_path);
+                process::exit(1);
+            }
+        }
+    }
+
+    // load pacing cues
+    let cues = match &config.cues {
+        Some(cue_path) => match cues::load(cue_path) {
+            Ok(cues) => {
+                println!("[^.^] Loaded {} cue(s) from {:?}", cues.len(), cue_path);
+                cues
+            }
+            Err(e) => {
+                eprintln!("[-.-] Could not load cues from {:?}: {e}", cue_path);
+                process::exit(1);
+            }
+        },
+        None => Vec::new(),
+    };
+    let timings = cues::schedule(&cues, &keyframes.bars());
+
     // render sequence
     let start = std::time::Instant::now();
-    engine::render(transformation);
+    engine::render(
+        engine::Piece {
+            chords: keyframes.chords.clone(),
+            origin,
+            motions: transformation,
+            durations: keyframes.durations(),
+            seconds: keyframes.seconds(),
+            bars: keyframes.bars(),
+            beats: keyframes.beats(),
+            timings,
+            spans: sequences,
+            cadences,
+            movements: movement_spans,
+            motion_types,
+            step_sizes,
+            references,
+            overlays,
+            axes: mapper.axes(),
+        },
+        &config,
+    );
     let elapsed = start.elapsed().as_secs_f32();
     println!("Time spent animating: {elapsed}");
 }
@@ -0,0 +1,207 @@
+use crate::config::Config;
+use crate::transformation::{self, Chord, Matrix, MotionVec};
+
+// A way of placing a chord sequence in space. map() returns one motion
+// per step and origin() where the first chord sits, so mappings giving
+// absolute locations and pure voice-motion mappings share the engine.
+pub trait ChordSpaceMapping {
+    fn map(&self, frames: &[Chord]) -> Vec<MotionVec>;
+
+    fn origin(&self, _frames: &[Chord]) -> MotionVec {
+        [0.0; 4]
+    }
+
+    // What each component of the motion vectors measures, [w, x, y, z]
+    fn axes(&self) -> [String; 4];
+}
+
+// Voice motion through the basis matrix (the original decomposition).
+// In absolute mode the path starts where the first chord sits relative to
+// the middle C voicing, so the same chord lands on the same point in every
+// piece; otherwise every piece starts at the origin.
+struct Contrary {
+    basis: Matrix,
+    weights: [f32; 4],
+    pitch_class: bool,
+    absolute: bool,
+    transposition_invariant: bool,
+}
+
+impl Contrary {
+    fn frames(&self, frames: &[Chord]) -> Vec<Chord> {
+        if self.pitch_class {
+            transformation::fold_pitch_classes(frames)
+        } else {
+            frames.to_vec()
+        }
+    }
+
+    // Basis images of every step, optionally without their transposition part
+    fn motions(&self, frames: Vec<Chord>) -> Vec<MotionVec> {
+        if self.transposition_invariant {
+            let scale = transformation::TRANSPOSITION_SCALE as f32;
+            let motions = transformation::convert_without_transposition(&frames, self.basis);
+            transformation::weigh(&motions, self.weights.map(|w| w / scale))
+        } else {
+            let motions = transformation::convert(frames, self.basis);
+            transformation::weigh(&motions, self.weights)
+        }
+    }
+}
+
+impl ChordSpaceMapping for Contrary {
+    fn map(&self, frames: &[Chord]) -> Vec<MotionVec> {
+        self.motions(self.frames(frames))
+    }
+
+    fn origin(&self, frames: &[Chord]) -> MotionVec {
+        if !self.absolute {
+            return [0.0; 4];
+        }
+        let reference = self.frames(&[transformation::MIDDLE_C])[0];
+        let first = self.frames(&frames[..1])[0];
+        self.motions(vec![reference, first])[0]
+    }
+
+    fn axes(&self) -> [String; 4] {
+        self.basis.map(transformation::axis_label)
+    }
+}
+
+// T⁴/S₄ orbifold coordinates under the basis
+struct Orbifold {
+    basis: Matrix,
+    weights: [f32; 4],
+}
+
+impl ChordSpaceMapping for Orbifold {
+    fn map(&self, frames: &[Chord]) -> Vec<MotionVec> {
+        let motions = transformation::convert(transformation::fold_orbifold(frames), self.basis);
+        transformation::weigh(&motions, self.weights)
+    }
+
+    fn origin(&self, frames: &[Chord]) -> MotionVec {
+        let first = transformation::fold_orbifold(&frames[..1]);
+        let origin = transformation::position(first[0], self.basis);
+        transformation::weigh(&[origin], self.weights)[0]
+    }
+
+    fn axes(&self) -> [String; 4] {
+        self.basis.map(transformation::axis_label)
+    }
+}
+
+// Root on the circle of fifths, quality as height
+struct Fifths {
+    weights: [f32; 4],
+}
+
+impl ChordSpaceMapping for Fifths {
+    fn map(&self, frames: &[Chord]) -> Vec<MotionVec> {
+        let motions = transformation::deltas(&transformation::fifths_points(frames));
+        transformation::weigh(&motions, self.weights)
+    }
+
+    fn origin(&self, frames: &[Chord]) -> MotionVec {
+        let origin = transformation::fifths_points(&frames[..1]);
+        transformation::weigh(&origin, self.weights)[0]
+    }
+
+    fn axes(&self) -> [String; 4] {
+        [
+            "summed pitch",
+            "fifths (sin)",
+            "chord quality",
+            "fifths (cos)",
+        ]
+        .map(String::from)
+    }
+}
+
+// Phases of the f5 and f3 Fourier coefficients on a torus
+struct Dft {
+    weights: [f32; 4],
+}
+
+impl Dft {
+    fn points(&self, frames: &[Chord]) -> Vec<MotionVec> {
+        transformation::dft_points(frames)
+            .iter()
+            .map(|p| [0, 1, 2, 3].map(|j| p[j] * self.weights[j]))
+            .collect()
+    }
+}
+
+impl ChordSpaceMapping for Dft {
+    fn map(&self, frames: &[Chord]) -> Vec<MotionVec> {
+        self.points(frames)
+            .windows(2)
+            .map(|w| [0, 1, 2, 3].map(|j| w[1][j] - w[0][j]))
+            .collect()
+    }
+
+    fn origin(&self, frames: &[Chord]) -> MotionVec {
+        self.points(&frames[..1])[0]
+    }
+
+    fn axes(&self) -> [String; 4] {
+        [
+            "summed pitch",
+            "f5 phase (cos)",
+            "f3 phase",
+            "f5 phase (sin)",
+        ]
+        .map(String::from)
+    }
+}
+
+type Constructor = fn(&Config) -> Box<dyn ChordSpaceMapping>;
+
+fn contrary(config: &Config) -> Box<dyn ChordSpaceMapping> {
+    Box::new(Contrary {
+        basis: config.matrix,
+        weights: config.weights,
+        pitch_class: config.pitch_class,
+        absolute: config.absolute,
+        transposition_invariant: config.transposition_invariant,
+    })
+}
+
+fn orbifold(config: &Config) -> Box<dyn ChordSpaceMapping> {
+    Box::new(Orbifold {
+        basis: config.matrix,
+        weights: config.weights,
+    })
+}
+
+fn fifths(config: &Config) -> Box<dyn ChordSpaceMapping> {
+    Box::new(Fifths {
+        weights: config.weights,
+    })
+}
+
+fn dft(config: &Config) -> Box<dyn ChordSpaceMapping> {
+    Box::new(Dft {
+        weights: config.weights,
+    })
+}
+
+// Every mapping selectable with --mapping, first is the default
+const REGISTRY: &[(&str, Constructor)] = &[
+    ("contrary", contrary),
+    ("orbifold", orbifold),
+    ("fifths", fifths),
+    ("dft", dft),
+];
+
+pub fn names() -> Vec<&'static str> {
+    REGISTRY.iter().map(|(name, _)| *name).collect()
+}
+
+// Build the named mapping for this configuration
+pub fn create(name: &str, config: &Config) -> Option<Box<dyn ChordSpaceMapping>> {
+    REGISTRY
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, constructor)| constructor(config))
+}
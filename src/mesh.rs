@@ -0,0 +1,144 @@
+use crate::rgba::Rgb;
+use kiss3d::nalgebra::Vector3;
+use std::fs;
+use std::path::Path;
+
+const TUBE_SIDES: usize = 8; // Vertices around each ring of the tube
+
+// Colored vertices joined by faces (tube) or by one polyline
+struct Mesh {
+    vertices: Vec<([f32; 3], Rgb)>, // Position and color
+    faces: Vec<Vec<usize>>,         // Vertex indices of each face
+    polyline: Vec<usize>,           // Vertex indices of the line, if any
+}
+
+// Write the trajectory as OBJ or PLY, chosen by the file extension. A
+// positive radius wraps the path in a closed tube, zero keeps a polyline.
+pub fn export(path: &Path, points: &[[f32; 3]], colors: &[Rgb], radius: f32) -> Result<(), String> {
+    let mesh = if radius > 0.0 {
+        tube(points, colors, radius)
+    } else {
+        Mesh {
+            vertices: points.iter().copied().zip(colors.iter().copied()).collect(),
+            faces: Vec::new(),
+            polyline: (0..points.len()).collect(),
+        }
+    };
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase());
+    let text = match extension.as_deref() {
+        Some("obj") => obj(&mesh),
+        Some("ply") => ply(&mesh),
+        _ => return Err(format!("{}: expected a .obj or .ply file", path.display())),
+    };
+    fs::write(path, text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+// Sweep a ring along the path, keeping each ring's orientation as close to
+// the previous one as possible so the tube does not twist
+fn tube(points: &[[f32; 3]], colors: &[Rgb], radius: f32) -> Mesh {
+    let at = |i: usize| Vector3::from(points[i]);
+    let mut vertices = Vec::new();
+    let mut tangent = Vector3::x();
+    let mut normal = Vector3::y();
+
+    for (i, &color) in colors.iter().enumerate().take(points.len()) {
+        // Held chords repeat a point; keep the previous direction there
+        let ahead = at((i + 1).min(points.len() - 1)) - at(i.saturating_sub(1));
+        if let Some(direction) = ahead.try_normalize(1e-6) {
+            tangent = direction;
+        }
+        let projected = normal - tangent * normal.dot(&tangent);
+        normal = projected.try_normalize(1e-6).unwrap_or_else(|| {
+            let side = if tangent.x.abs() < 0.9 {
+                Vector3::x()
+            } else {
+                Vector3::y()
+            };
+            tangent.cross(&side).normalize()
+        });
+        let binormal = tangent.cross(&normal);
+
+        for side in 0..TUBE_SIDES {
+            let angle = side as f32 / TUBE_SIDES as f32 * std::f32::consts::TAU;
+            let offset = (normal * angle.cos() + binormal * angle.sin()) * radius;
+            vertices.push(((at(i) + offset).into(), color));
+        }
+    }
+
+    let rings = vertices.len() / TUBE_SIDES;
+    let mut faces = Vec::new();
+    for ring in 0..rings.saturating_sub(1) {
+        for side in 0..TUBE_SIDES {
+            let next = (side + 1) % TUBE_SIDES;
+            faces.push(vec![
+                ring * TUBE_SIDES + side,
+                ring * TUBE_SIDES + next,
+                (ring + 1) * TUBE_SIDES + next,
+                (ring + 1) * TUBE_SIDES + side,
+            ]);
+        }
+    }
+
+    // Cap both ends so the mesh is closed (printable)
+    if rings > 0 {
+        faces.push((0..TUBE_SIDES).rev().collect());
+        faces.push(((rings - 1) * TUBE_SIDES..rings * TUBE_SIDES).collect());
+    }
+
+    Mesh {
+        vertices,
+        faces,
+        polyline: Vec::new(),
+    }
+}
+
+// Wavefront OBJ with the common "v x y z r g b" vertex-color extension
+fn obj(mesh: &Mesh) -> String {
+    let mut out = String::from("# Chord-space trajectory\n");
+    for ([x, y, z], (r, g, b)) in &mesh.vertices {
+        out.push_str(&format!("v {x} {y} {z} {r:.3} {g:.3} {b:.3}\n"));
+    }
+    for face in &mesh.faces {
+        let indices: Vec<String> = face.iter().map(|i| (i + 1).to_string()).collect();
+        out.push_str(&format!("f {}\n", indices.join(" ")));
+    }
+    if !mesh.polyline.is_empty() {
+        let indices: Vec<String> = mesh.polyline.iter().map(|i| (i + 1).to_string()).collect();
+        out.push_str(&format!("l {}\n", indices.join(" ")));
+    }
+    out
+}
+
+// ASCII PLY with 8-bit vertex colors; the polyline becomes edges
+fn ply(mesh: &Mesh) -> String {
+    let edges = mesh.polyline.len().saturating_sub(1);
+    let mut out = format!(
+        "ply\nformat ascii 1.0\ncomment Chord-space trajectory\n\
+         element vertex {}\nproperty float x\nproperty float y\nproperty float z\n\
+         property uchar red\nproperty uchar green\nproperty uchar blue\n\
+         element face {}\nproperty list uchar int vertex_indices\n\
+         element edge {edges}\nproperty int vertex1\nproperty int vertex2\nend_header\n",
+        mesh.vertices.len(),
+        mesh.faces.len()
+    );
+    let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    for ([x, y, z], (r, g, b)) in &mesh.vertices {
+        out.push_str(&format!(
+            "{x} {y} {z} {} {} {}\n",
+            byte(*r),
+            byte(*g),
+            byte(*b)
+        ));
+    }
+    for face in &mesh.faces {
+        let indices: Vec<String> = face.iter().map(usize::to_string).collect();
+        out.push_str(&format!("{} {}\n", face.len(), indices.join(" ")));
+    }
+    for pair in mesh.polyline.windows(2) {
+        out.push_str(&format!("{} {}\n", pair[0], pair[1]));
+    }
+    out
+}
@@ -1,10 +1,192 @@
+use midly::Format;
+use midly::MetaMessage;
 use midly::MidiMessage;
 use midly::Smf;
+use midly::Track;
 use midly::TrackEventKind;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::Path;
 
-pub fn parse(path: &Path) -> Result<Vec<[i32; 4]>, Box<dyn std::error::Error>> {
+// Note onsets of a single voice, keyed by absolute tick
+type NoteMap = BTreeMap<u32, i32>;
+
+// Tempo in effect until the first Set Tempo event (120 BPM)
+const DEFAULT_TEMPO: u32 = 500_000;
+
+// Time signature in effect until the first Time Signature event
+const DEFAULT_METER: (u32, u32) = (4, 4);
+
+// How to complete the chord when fewer than four usable voices exist
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fill {
+    BassOctave, // Double the lowest voice an octave down
+    Soprano,    // Double the highest voice
+    Collapse,   // Hold a fixed pitch; adds no motion but counts as a chord tone
+}
+
+impl Fill {
+    pub fn from_name(name: &str) -> Option<Fill> {
+        match name {
+            "bass-octave" => Some(Fill::BassOctave),
+            "soprano" => Some(Fill::Soprano),
+            "collapse" => Some(Fill::Collapse),
+            _ => None,
+        }
+    }
+}
+
+// How chords are sampled from the voice lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyframeMode {
+    Grid,   // One keyframe per 16th note
+    Events, // One keyframe whenever any voice changes pitch
+}
+
+impl KeyframeMode {
+    pub fn from_name(name: &str) -> Option<KeyframeMode> {
+        match name {
+            "grid" => Some(KeyframeMode::Grid),
+            "events" => Some(KeyframeMode::Events),
+            _ => None,
+        }
+    }
+}
+
+// Chord frames together with the tick at which each one starts
+pub struct Keyframes {
+    pub chords: Vec<[i32; 4]>,
+    pub ticks: Vec<u32>,
+    pub ticks_per_16th: u32,
+    pub tempo: Vec<(u32, u32)>, // Tempo changes as (tick, microseconds per quarter)
+    pub meter: Vec<(u32, u32, u32)>, // Time signatures as (tick, numerator, denominator)
+}
+
+impl Keyframes {
+    // Length of each chord-to-chord motion, in 16th notes
+    pub fn durations(&self) -> Vec<f32> {
+        self.ticks
+            .windows(2)
+            .map(|w| (w[1] - w[0]) as f32 / self.ticks_per_16th as f32)
+            .collect()
+    }
+
+    // Length of each chord-to-chord motion in seconds, following the tempo map
+    pub fn seconds(&self) -> Vec<f32> {
+        let times: Vec<f64> = self.ticks.iter().map(|&tick| self.time_at(tick)).collect();
+        times.windows(2).map(|w| (w[1] - w[0]) as f32).collect()
+    }
+
+    // Seconds from the start of the piece to a tick
+    fn time_at(&self, tick: u32) -> f64 {
+        let ticks_per_quarter = self.ticks_per_16th.max(1) as f64 * 4.0;
+        let mut seconds = 0.0;
+        let mut last_tick = 0;
+        let mut tempo = DEFAULT_TEMPO;
+        for &(change, us_per_quarter) in &self.tempo {
+            if change >= tick {
+                break;
+            }
+            seconds += (change - last_tick) as f64 * tempo as f64 / 1e6 / ticks_per_quarter;
+            last_tick = change;
+            tempo = us_per_quarter;
+        }
+        seconds + (tick - last_tick) as f64 * tempo as f64 / 1e6 / ticks_per_quarter
+    }
+
+    // Bar (1-based) in which each chord starts, following the time signature
+    pub fn bars(&self) -> Vec<usize> {
+        self.ticks
+            .iter()
+            .map(|&tick| self.measure_at(tick).0)
+            .collect()
+    }
+
+    // Beat (1-based, in the time signature's beat unit) at which each chord starts
+    pub fn beats(&self) -> Vec<f32> {
+        self.ticks
+            .iter()
+            .map(|&tick| self.measure_at(tick).1)
+            .collect()
+    }
+
+    // Ticks in one beat and in one bar of a time signature
+    fn meter_ticks(&self, (numerator, denominator): (u32, u32)) -> (u32, u32) {
+        let beat = (self.ticks_per_16th * 16 / denominator.max(1)).max(1);
+        (beat, beat * numerator.max(1))
+    }
+
+    // Bar and beat at a tick, plus the tick its bar started at. A meter
+    // change part way through a bar starts a new bar.
+    fn measure_at(&self, tick: u32) -> (usize, f32, u32) {
+        let mut bar = 1;
+        let mut start = 0;
+        let mut meter = DEFAULT_METER;
+        for &(change, numerator, denominator) in &self.meter {
+            if change > tick {
+                break;
+            }
+            let (_, length) = self.meter_ticks(meter);
+            bar += (change - start).div_ceil(length) as usize;
+            start = change;
+            meter = (numerator, denominator);
+        }
+        let (beat, length) = self.meter_ticks(meter);
+        let into = tick - start;
+        let bar_start = start + into / length * length;
+        (
+            bar + (into / length) as usize,
+            (tick - bar_start) as f32 / beat as f32 + 1.0,
+            bar_start,
+        )
+    }
+
+    // Continue with another piece from the next bar line, rescaling its
+    // ticks to this piece's resolution
+    pub fn append(&mut self, other: Keyframes) {
+        let offset = self.ticks.last().map_or(0, |&tick| {
+            let (_, _, bar_start) = self.measure_at(tick);
+            let meter = self
+                .meter
+                .iter()
+                .rev()
+                .find(|&&(change, _, _)| change <= tick)
+                .map_or(DEFAULT_METER, |&(_, numerator, denominator)| {
+                    (numerator, denominator)
+                });
+            bar_start + self.meter_ticks(meter).1
+        });
+        let rescale = |tick: u32| {
+            offset + (tick as u64 * self.ticks_per_16th as u64 / other.ticks_per_16th as u64) as u32
+        };
+        // The appended piece starts at the default tempo until it sets its own
+        self.tempo.push((offset, DEFAULT_TEMPO));
+        self.tempo.extend(
+            other
+                .tempo
+                .iter()
+                .map(|&(tick, us_per_quarter)| (rescale(tick), us_per_quarter)),
+        );
+        // ...and in 4/4 until it sets its own time signature
+        self.meter.push((offset, DEFAULT_METER.0, DEFAULT_METER.1));
+        self.meter.extend(
+            other
+                .meter
+                .iter()
+                .map(|&(tick, numerator, denominator)| (rescale(tick), numerator, denominator)),
+        );
+        self.chords.extend(other.chords);
+        self.ticks
+            .extend(other.ticks.iter().map(|&tick| rescale(tick)));
+    }
+}
+
+pub fn parse(
+    path: &Path,
+    sequence: Option<usize>,
+    fill: Fill,
+    mode: KeyframeMode,
+) -> Result<Keyframes, Box<dyn std::error::Error>> {
     let data = fs::read(path)?;
     let smf = Smf::parse(&data)?;
 
@@ -14,39 +196,251 @@ pub fn parse(path: &Path) -> Result<Vec<[i32; 4]>, Box<dyn std::error::Error>> {
     };
     let ticks_per_16th = tpq / 4;
 
-    // Each track becomes one voice line
-    let mut voice_timelines = vec![vec![]; 4];
-    for (track_idx, track) in smf.tracks.iter().take(4).enumerate() {
-        let mut abs_tick = 0u32;
-        let mut notes_by_tick = std::collections::BTreeMap::new();
+    if smf.header.format == Format::Sequential {
+        // Type 2: every track is an independent sequence whose voices are
+        // told apart by channel. Play the selected one, or all back to back.
+        let selected: Vec<&Track> = match sequence {
+            Some(n) => match smf.tracks.get(n.wrapping_sub(1)) {
+                Some(track) => vec![track],
+                None => {
+                    return Err(format!(
+                        "Sequence {} does not exist (file has {})",
+                        n,
+                        smf.tracks.len()
+                    )
+                    .into());
+                }
+            },
+            None => smf.tracks.iter().collect(),
+        };
 
-        for event in track {
-            abs_tick += event.delta.as_int();
+        let mut chords = Vec::new();
+        let mut ticks = Vec::new();
+        let mut tempo = Vec::new();
+        let mut meter = Vec::new();
+        let mut offset = 0;
+        for track in selected {
+            tempo.push((offset, DEFAULT_TEMPO));
+            tempo.extend(
+                tempo_map(track)
+                    .into_iter()
+                    .map(|(tick, us_per_quarter)| (tick + offset, us_per_quarter)),
+            );
+            meter.push((offset, DEFAULT_METER.0, DEFAULT_METER.1));
+            meter.extend(
+                meter_map(track)
+                    .into_iter()
+                    .map(|(tick, numerator, denominator)| (tick + offset, numerator, denominator)),
+            );
+            let (seq_chords, seq_ticks) =
+                align(channel_note_maps(track), ticks_per_16th, fill, mode);
+            chords.extend(seq_chords);
+            ticks.extend(seq_ticks.iter().map(|tick| tick + offset));
 
-            if let TrackEventKind::Midi { message, .. } = event.kind {
-                if let MidiMessage::NoteOn { key, vel } = message {
-                    if vel > 0 {
-                        notes_by_tick.insert(abs_tick, key.as_int() as i32);
-                    }
-                }
-            }
+            // The next sequence starts one 16th after this one ends, which is
+            // after its last event as well as its last keyframe
+            let end = ticks.last().copied().unwrap_or(offset);
+            offset = end.max(offset + track_length(track)) + ticks_per_16th;
+        }
+        sort_changes(&mut tempo, |&(tick, _)| tick);
+        sort_changes(&mut meter, |&(tick, _, _)| tick);
+        if chords.is_empty() {
+            return Err("No notes found".into());
         }
+        return Ok(Keyframes {
+            chords,
+            ticks,
+            ticks_per_16th,
+            tempo,
+            meter,
+        });
+    }
 
-        // Now build the timeline per 16th slot, sustaining notes
-        let mut tick = 0;
-        let max_tick = *notes_by_tick.keys().last().unwrap_or(&0);
-        let mut last_note = 0;
+    if sequence.is_some() {
+        return Err("--sequence only applies to type 2 (sequential) MIDI files".into());
+    }
 
-        while tick <= max_tick {
-            if let Some(&note) = notes_by_tick.get(&tick) {
-                last_note = note;
-            }
+    // Each track with notes becomes one voice line
+    let voices: Vec<NoteMap> = smf
+        .tracks
+        .iter()
+        .map(note_map)
+        .filter(|notes| !notes.is_empty())
+        .take(4)
+        .collect();
+    if voices.is_empty() {
+        return Err("No notes found".into());
+    }
+    let (chords, ticks) = align(voices, ticks_per_16th, fill, mode);
+
+    // Tempo events usually live in the first track but may appear in any
+    let mut tempo: Vec<(u32, u32)> = smf.tracks.iter().flat_map(tempo_map).collect();
+    sort_changes(&mut tempo, |&(tick, _)| tick);
+    let mut meter: Vec<(u32, u32, u32)> = smf.tracks.iter().flat_map(meter_map).collect();
+    sort_changes(&mut meter, |&(tick, _, _)| tick);
+    Ok(Keyframes {
+        chords,
+        ticks,
+        ticks_per_16th,
+        tempo,
+        meter,
+    })
+}
+
+// Ticks from the start of a track to its last event
+fn track_length(track: &Track) -> u32 {
+    track.iter().map(|event| event.delta.as_int()).sum()
+}
+
+// Order tempo or meter changes by tick, keeping only the later of two
+// changes at the same tick
+fn sort_changes<T: Copy>(changes: &mut Vec<T>, tick: impl Fn(&T) -> u32) {
+    changes.sort_by_key(&tick);
+    changes.dedup_by(|later, earlier| {
+        let same = tick(later) == tick(earlier);
+        if same {
+            *earlier = *later;
+        }
+        same
+    });
+}
+
+// Collect Set Tempo events of a track as (tick, microseconds per quarter)
+fn tempo_map(track: &Track) -> Vec<(u32, u32)> {
+    let mut abs_tick = 0u32;
+    let mut changes = Vec::new();
+
+    for event in track {
+        abs_tick += event.delta.as_int();
+
+        if let TrackEventKind::Meta(MetaMessage::Tempo(us_per_quarter)) = event.kind {
+            changes.push((abs_tick, us_per_quarter.as_int()));
+        }
+    }
+
+    changes
+}
+
+// Collect Time Signature events of a track as (tick, numerator, denominator)
+fn meter_map(track: &Track) -> Vec<(u32, u32, u32)> {
+    let mut abs_tick = 0u32;
+    let mut changes = Vec::new();
+
+    for event in track {
+        abs_tick += event.delta.as_int();
+
+        if let TrackEventKind::Meta(MetaMessage::TimeSignature(numerator, denominator_pow, _, _)) =
+            event.kind
+        {
+            changes.push((abs_tick, numerator as u32, 1u32 << denominator_pow.min(6)));
+        }
+    }
+
+    changes
+}
+
+// Collect note onsets of a whole track as one voice
+fn note_map(track: &Track) -> NoteMap {
+    let mut abs_tick = 0u32;
+    let mut notes_by_tick = NoteMap::new();
+
+    for event in track {
+        abs_tick += event.delta.as_int();
+
+        if let TrackEventKind::Midi {
+            message: MidiMessage::NoteOn { key, vel },
+            ..
+        } = event.kind
+            && vel > 0
+        {
+            notes_by_tick.insert(abs_tick, key.as_int() as i32);
+        }
+    }
+
+    notes_by_tick
+}
+
+// Collect note onsets per channel, up to four voices in order of first appearance
+fn channel_note_maps(track: &Track) -> Vec<NoteMap> {
+    let mut abs_tick = 0u32;
+    let mut channels: Vec<u8> = Vec::new();
+    let mut voices: Vec<NoteMap> = Vec::new();
+
+    for event in track {
+        abs_tick += event.delta.as_int();
 
-            voice_timelines[track_idx].push(last_note);
-            tick += ticks_per_16th;
+        if let TrackEventKind::Midi {
+            channel,
+            message: MidiMessage::NoteOn { key, vel },
+        } = event.kind
+            && vel > 0
+        {
+            let channel = channel.as_int();
+            let voice = match channels.iter().position(|&c| c == channel) {
+                Some(voice) => voice,
+                None if channels.len() < 4 => {
+                    channels.push(channel);
+                    voices.push(NoteMap::new());
+                    channels.len() - 1
+                }
+                None => continue,
+            };
+            voices[voice].insert(abs_tick, key.as_int() as i32);
         }
     }
 
+    voices
+}
+
+// Sample voices into chord frames, returning the frames and their start ticks
+fn align(
+    voices: Vec<NoteMap>,
+    ticks_per_16th: u32,
+    fill: Fill,
+    mode: KeyframeMode,
+) -> (Vec<[i32; 4]>, Vec<u32>) {
+    if voices.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+
+    let mut voice_timelines = vec![vec![]; voices.len().min(4)];
+    let sample_ticks: Vec<u32> = match mode {
+        KeyframeMode::Grid => {
+            for (voice_idx, notes_by_tick) in voices.iter().take(4).enumerate() {
+                // Now build the timeline per 16th slot, sustaining notes
+                let mut tick = 0;
+                let max_tick = *notes_by_tick.keys().last().unwrap_or(&0);
+                let mut last_note = 0;
+
+                while tick <= max_tick {
+                    if let Some(&note) = notes_by_tick.get(&tick) {
+                        last_note = note;
+                    }
+
+                    voice_timelines[voice_idx].push(last_note);
+                    tick += ticks_per_16th;
+                }
+            }
+            let len = voice_timelines.iter().map(Vec::len).max().unwrap_or(0) as u32;
+            (0..len).map(|i| i * ticks_per_16th).collect()
+        }
+        KeyframeMode::Events => {
+            // Sample every onset of any voice, sustaining the others
+            let onsets: BTreeSet<u32> = voices
+                .iter()
+                .take(4)
+                .flat_map(|notes| notes.keys().copied())
+                .collect();
+            for (voice_idx, notes_by_tick) in voices.iter().take(4).enumerate() {
+                for &tick in &onsets {
+                    let note = notes_by_tick.range(..=tick).next_back();
+                    voice_timelines[voice_idx].push(note.map_or(0, |(_, &note)| note));
+                }
+            }
+            onsets.into_iter().collect()
+        }
+    };
+
     // Align all voices into a single Vec<[i32; 4]>
     let len = voice_timelines.iter().map(Vec::len).max().unwrap_or(0);
 
@@ -62,10 +456,36 @@ pub fn parse(path: &Path) -> Result<Vec<[i32; 4]>, Box<dyn std::error::Error>> {
             }
         }
     }
+
+    // Complete the chord from the real voices
+    if voice_timelines.len() < 4 {
+        println!(
+            "[^.^] Only {} usable voice(s), filling with {:?}",
+            voice_timelines.len(),
+            fill
+        );
+    }
+    while voice_timelines.len() < 4 {
+        match fill {
+            Fill::BassOctave => {
+                let bass = voice_timelines.last().unwrap().clone();
+                voice_timelines.push(bass.iter().map(|note| note - 12).collect());
+            }
+            Fill::Soprano => {
+                let soprano = voice_timelines[0].clone();
+                voice_timelines.insert(0, soprano);
+            }
+            Fill::Collapse => {
+                let bass = voice_timelines.last().unwrap();
+                voice_timelines.push(vec![bass[0]; bass.len()]);
+            }
+        }
+    }
+
     let mut combined = Vec::with_capacity(len);
     for i in 0..len {
         let frame = [
-            *voice_timelines.get(0).and_then(|v| v.get(i)).unwrap_or(&0),
+            *voice_timelines.first().and_then(|v| v.get(i)).unwrap_or(&0),
             *voice_timelines.get(1).and_then(|v| v.get(i)).unwrap_or(&0),
             *voice_timelines.get(2).and_then(|v| v.get(i)).unwrap_or(&0),
             *voice_timelines.get(3).and_then(|v| v.get(i)).unwrap_or(&0),
@@ -73,5 +493,211 @@ pub fn parse(path: &Path) -> Result<Vec<[i32; 4]>, Box<dyn std::error::Error>> {
         combined.push(frame);
     }
 
-    Ok(combined)
+    // Only keep frames where some voice actually moves
+    let mut ticks = sample_ticks;
+    if mode == KeyframeMode::Events {
+        let mut kept = 0;
+        for i in 0..combined.len() {
+            if kept == 0 || combined[i] != combined[kept - 1] {
+                combined[kept] = combined[i];
+                ticks[kept] = ticks[i];
+                kept += 1;
+            }
+        }
+        combined.truncate(kept);
+        ticks.truncate(kept);
+    }
+
+    (combined, ticks)
+}
+
+// Print a diagnostic overview of a MIDI file without rendering it
+pub fn inspect(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let data = fs::read(path)?;
+    let smf = Smf::parse(&data)?;
+
+    println!("🔎 {:?}", path);
+    println!("Format: {:?}", smf.header.format);
+    match smf.header.timing {
+        midly::Timing::Metrical(t) => println!("Timing: metrical, {} ticks per quarter", t),
+        midly::Timing::Timecode(fps, sub) => println!(
+            "Timing: timecode, {} fps x {} subframes (unsupported)",
+            fps.as_f32(),
+            sub
+        ),
+    }
+    println!("Tracks: {}", smf.tracks.len());
+
+    let mut tempo_changes = Vec::new();
+    let mut meter_changes = Vec::new();
+    for (track_idx, track) in smf.tracks.iter().enumerate() {
+        let mut abs_tick = 0u32;
+        let mut name = String::new();
+        let mut channels = BTreeSet::new();
+        let mut notes = 0;
+        let mut range: Option<(u8, u8)> = None;
+
+        for event in track {
+            abs_tick += event.delta.as_int();
+            match event.kind {
+                TrackEventKind::Midi { channel, message } => {
+                    channels.insert(channel.as_int());
+                    if let MidiMessage::NoteOn { key, vel } = message
+                        && vel > 0
+                    {
+                        let key = key.as_int();
+                        notes += 1;
+                        range = Some(match range {
+                            Some((lo, hi)) => (lo.min(key), hi.max(key)),
+                            None => (key, key),
+                        });
+                    }
+                }
+                TrackEventKind::Meta(MetaMessage::TrackName(bytes)) => {
+                    name = String::from_utf8_lossy(bytes).into_owned();
+                }
+                TrackEventKind::Meta(MetaMessage::Tempo(us_per_quarter)) => {
+                    tempo_changes.push((abs_tick, 60_000_000.0 / us_per_quarter.as_int() as f32));
+                }
+                TrackEventKind::Meta(MetaMessage::TimeSignature(num, denom_pow, _, _)) => {
+                    // Clamped as in meter_map, so a corrupt exponent cannot overflow
+                    meter_changes.push((abs_tick, num, 1u32 << denom_pow.min(6)));
+                }
+                _ => {}
+            }
+        }
+
+        let range = match range {
+            Some((lo, hi)) => format!("{lo}-{hi}"),
+            None => "-".into(),
+        };
+        println!(
+            "  Track {:02} {:<20} events: {:5}  notes: {:5}  range: {:7}  channels: {:?}",
+            track_idx,
+            format!("\"{}\"", name),
+            track.len(),
+            notes,
+            range,
+            channels
+        );
+    }
+
+    println!("Tempo changes:");
+    if tempo_changes.is_empty() {
+        println!("  none (120 BPM assumed)");
+    }
+    for (tick, bpm) in &tempo_changes {
+        println!("  tick {:8}: {:.2} BPM", tick, bpm);
+    }
+    println!("Time signatures:");
+    if meter_changes.is_empty() {
+        println!("  none (4/4 assumed)");
+    }
+    for (tick, num, denom) in &meter_changes {
+        println!("  tick {:8}: {}/{}", tick, num, denom);
+    }
+
+    // Mirror the voice selection in parse()
+    println!("Voice selection:");
+    if smf.header.format == Format::Sequential {
+        for (track_idx, track) in smf.tracks.iter().enumerate() {
+            println!(
+                "  Sequence {}: track {:02}, {} voice(s) by channel",
+                track_idx + 1,
+                track_idx,
+                channel_note_maps(track).len()
+            );
+        }
+    } else {
+        let selected: Vec<usize> = smf
+            .tracks
+            .iter()
+            .enumerate()
+            .filter(|(_, track)| !note_map(track).is_empty())
+            .map(|(track_idx, _)| track_idx)
+            .take(4)
+            .collect();
+        for (voice, track_idx) in selected.iter().enumerate() {
+            println!("  Voice {}: track {:02}", voice, track_idx);
+        }
+        if selected.len() < 4 {
+            println!(
+                "  {} voice(s) missing, completed by --fill",
+                4 - selected.len()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use midly::num::{u4, u7, u15, u24, u28};
+    use midly::{Header, Timing, TrackEvent};
+
+    fn event(delta: u32, kind: TrackEventKind<'static>) -> TrackEvent<'static> {
+        TrackEvent {
+            delta: u28::new(delta),
+            kind,
+        }
+    }
+
+    // A four-voice chord on channels 0-3 held for a quarter (480 ticks)
+    fn chord(keys: [u8; 4]) -> Vec<TrackEvent<'static>> {
+        let note = |delta, channel: u8, key: u8, vel| {
+            let message = MidiMessage::NoteOn {
+                key: u7::new(key),
+                vel: u7::new(vel),
+            };
+            event(
+                delta,
+                TrackEventKind::Midi {
+                    channel: u4::new(channel),
+                    message,
+                },
+            )
+        };
+        let mut events: Vec<_> = (0..4).map(|v| note(0, v, keys[v as usize], 80)).collect();
+        events.extend((0..4).map(|v| note(if v == 0 { 480 } else { 0 }, v, keys[v as usize], 0)));
+        events
+    }
+
+    #[test]
+    fn trailing_tempo_in_a_sequence_keeps_the_tempo_map_in_order() {
+        let tempo = |delta, us| {
+            event(
+                delta,
+                TrackEventKind::Meta(MetaMessage::Tempo(u24::new(us))),
+            )
+        };
+        let end = event(0, TrackEventKind::Meta(MetaMessage::EndOfTrack));
+        let mut first = chord([48, 55, 64, 72]);
+        first.push(tempo(1440, 400_000)); // Long after the last note
+        first.push(end);
+        let mut second = chord([50, 57, 65, 74]);
+        second.push(end);
+
+        let smf = Smf {
+            header: Header::new(Format::Sequential, Timing::Metrical(u15::new(480))),
+            tracks: vec![first, second],
+        };
+        let path = std::env::temp_dir().join("visual-trailing-tempo.mid");
+        smf.save(&path).unwrap();
+        let keyframes = parse(&path, None, Fill::Soprano, KeyframeMode::Events).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert!(keyframes.tempo.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(keyframes.meter.windows(2).all(|w| w[0].0 < w[1].0));
+        // The second sequence's chord comes after the first one's trailing tempo
+        assert!(keyframes.ticks.last().is_some_and(|&tick| tick > 1920));
+        assert!(
+            keyframes
+                .seconds()
+                .iter()
+                .all(|s| s.is_finite() && *s >= 0.0)
+        );
+        assert_eq!(keyframes.bars().len(), keyframes.ticks.len());
+    }
 }
@@ -0,0 +1,196 @@
+use crate::hud::planar;
+use kiss3d::event::{Action, Key, Modifiers, WindowEvent};
+use kiss3d::nalgebra::{Point2, Point3};
+use kiss3d::text::Font;
+use kiss3d::window::Window;
+use std::rc::Rc;
+
+const PANEL_WIDTH: f32 = 300.0; // Pixels, along the right edge of the window
+const ROW_HEIGHT: f32 = 44.0;
+const PANEL_MARGIN: f32 = 12.0;
+const TEXT_SIZE: f32 = 24.0;
+const TRACK_INSET: f32 = 14.0; // Horizontal inset of slider tracks within a row
+const PANEL_COLOR: (f32, f32, f32) = (0.5, 0.55, 0.65);
+const ACTIVE_COLOR: (f32, f32, f32) = (1.0, 0.85, 0.4);
+
+// Everything the panel can adjust or trigger
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Control {
+    Speed,      // Playback speed slider
+    Trail,      // Trail length slider
+    ColorScale, // Hue change per semitone of total motion
+    Projection, // Cycle the 4D projection
+    Pause,      // Pause or resume
+    Back,       // Seek one keyframe back
+    Forward,    // Seek one keyframe forward
+    Screenshot, // Save a PNG
+}
+
+impl Control {
+    fn is_slider(self) -> bool {
+        matches!(self, Control::Speed | Control::Trail | Control::ColorScale)
+    }
+
+    // Key event a button stands in for, so it behaves exactly like the key
+    pub fn shortcut(self) -> Option<WindowEvent> {
+        let (key, action) = match self {
+            Control::Pause => (Key::Space, Action::Release),
+            Control::Back => (Key::Left, Action::Press),
+            Control::Forward => (Key::Right, Action::Press),
+            Control::Screenshot => (Key::S, Action::Release),
+            Control::Projection | Control::Speed | Control::Trail | Control::ColorScale => {
+                return None;
+            }
+        };
+        Some(WindowEvent::Key(key, action, Modifiers::empty()))
+    }
+}
+
+// Top-to-bottom order of the rows
+const LAYOUT: [Control; 8] = [
+    Control::Speed,
+    Control::Trail,
+    Control::ColorScale,
+    Control::Projection,
+    Control::Pause,
+    Control::Back,
+    Control::Forward,
+    Control::Screenshot,
+];
+
+// What the user did with the panel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PanelEvent {
+    Slide(Control, f32), // Slider moved to this position (0..1)
+    Click(Control),      // Button pressed
+}
+
+// A small hand-drawn control panel: sliders and buttons drawn with planar
+// lines and text, driven by the same mouse events as picking
+#[derive(Default)]
+pub struct Panel {
+    pub visible: bool,         // Drawn and accepting clicks
+    dragging: Option<Control>, // Slider held down by the mouse
+}
+
+impl Panel {
+    // Row under a screen position (pixels, origin at the top left)
+    fn row_at(&self, cursor: (f64, f64), width: f32) -> Option<Control> {
+        let (x, y) = (cursor.0 as f32, cursor.1 as f32);
+        let left = width - PANEL_WIDTH - PANEL_MARGIN;
+        if !self.visible || x < left || x > width - PANEL_MARGIN || y < PANEL_MARGIN {
+            return None;
+        }
+        LAYOUT
+            .get(((y - PANEL_MARGIN) / ROW_HEIGHT) as usize)
+            .copied()
+    }
+
+    // Slider position under the cursor
+    fn fraction(cursor: (f64, f64), width: f32) -> f32 {
+        let left = width - PANEL_WIDTH - PANEL_MARGIN + TRACK_INSET;
+        let track = PANEL_WIDTH - 2.0 * TRACK_INSET;
+        ((cursor.0 as f32 - left) / track).clamp(0.0, 1.0)
+    }
+
+    // Pixels the panel takes from the top of the window, none when hidden
+    pub fn height(&self) -> f32 {
+        if self.visible {
+            2.0 * PANEL_MARGIN + LAYOUT.len() as f32 * ROW_HEIGHT
+        } else {
+            0.0
+        }
+    }
+
+    // Whether a click here belongs to the panel rather than the scene
+    pub fn contains(&self, cursor: (f64, f64), width: f32) -> bool {
+        self.row_at(cursor, width).is_some()
+    }
+
+    pub fn press(&mut self, cursor: (f64, f64), width: f32) -> Option<PanelEvent> {
+        let control = self.row_at(cursor, width)?;
+        if control.is_slider() {
+            self.dragging = Some(control);
+            Some(PanelEvent::Slide(control, Self::fraction(cursor, width)))
+        } else {
+            Some(PanelEvent::Click(control))
+        }
+    }
+
+    pub fn drag(&mut self, cursor: (f64, f64), width: f32) -> Option<PanelEvent> {
+        let control = self.dragging?;
+        Some(PanelEvent::Slide(control, Self::fraction(cursor, width)))
+    }
+
+    // Let go of a slider; returns whether one was held
+    pub fn release(&mut self) -> bool {
+        self.dragging.take().is_some()
+    }
+
+    // Draw every row; `state` gives each control's slider position (0..1)
+    // and caption
+    pub fn draw(
+        &self,
+        window: &mut Window,
+        font: &Rc<Font>,
+        state: impl Fn(Control) -> (f32, String),
+    ) {
+        if !self.visible {
+            return;
+        }
+        let width = window.width() as f32;
+        let left = width - PANEL_WIDTH - PANEL_MARGIN;
+        let right = width - PANEL_MARGIN;
+
+        for (row, &control) in LAYOUT.iter().enumerate() {
+            let top = PANEL_MARGIN + row as f32 * ROW_HEIGHT;
+            let bottom = top + ROW_HEIGHT - 6.0;
+            let (value, caption) = state(control);
+            let (r, g, b) = if self.dragging == Some(control) {
+                ACTIVE_COLOR
+            } else {
+                PANEL_COLOR
+            };
+            let color = Point3::new(r, g, b);
+
+            if control.is_slider() {
+                // Track along the bottom of the row, with a handle
+                let track_y = bottom - 6.0;
+                let track_left = left + TRACK_INSET;
+                let track_right = right - TRACK_INSET;
+                window.draw_planar_line(
+                    &planar(window, track_left, track_y),
+                    &planar(window, track_right, track_y),
+                    &color,
+                );
+                let handle = track_left + (track_right - track_left) * value.clamp(0.0, 1.0);
+                for dx in [-1.0, 0.0, 1.0] {
+                    window.draw_planar_line(
+                        &planar(window, handle + dx, track_y - 8.0),
+                        &planar(window, handle + dx, track_y + 8.0),
+                        &Point3::new(1.0, 1.0, 1.0),
+                    );
+                }
+            } else {
+                // Button outline
+                let corners = [
+                    planar(window, left, top),
+                    planar(window, right, top),
+                    planar(window, right, bottom),
+                    planar(window, left, bottom),
+                ];
+                for i in 0..4 {
+                    window.draw_planar_line(&corners[i], &corners[(i + 1) % 4], &color);
+                }
+            }
+
+            window.draw_text(
+                &caption,
+                &Point2::new(left + TRACK_INSET, top + 2.0),
+                TEXT_SIZE,
+                font,
+                &Point3::new(0.9, 0.9, 0.95),
+            );
+        }
+    }
+}
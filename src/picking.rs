@@ -0,0 +1,195 @@
+use kiss3d::camera::Camera;
+use kiss3d::context::Context;
+use kiss3d::nalgebra::{Matrix4, Point3};
+use kiss3d::ncollide3d::procedural;
+use kiss3d::resource::{
+    Effect, FramebufferManager, Mesh, RenderTarget, ShaderAttribute, ShaderUniform,
+};
+
+const PROXY_SUBDIVISIONS: (u32, u32) = (16, 8); // Resolution of the spheres drawn in the ID pass
+
+// Anything in the scene that can be clicked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SceneObject {
+    Sphere,          // The animated chord sphere
+    Keyframe(usize), // A reached keyframe on the trail
+    Span(usize),     // A sequence bracket, by span index
+    Cadence(usize),  // A cadence ring, by cadence index
+    Voice(usize),    // One of the per-voice spheres, top voice first
+    Origin,          // The origin marker
+}
+
+// Bounding sphere registered for one scene object
+struct Pickable {
+    object: SceneObject,
+    center: Point3<f32>,
+    radius: f32,
+}
+
+// Per-frame registry of pickable objects. Objects register their bounds
+// while being drawn, and a click renders them into an offscreen ID buffer,
+// each in a flat color encoding its index, then reads back the pixel under
+// the cursor; depth testing leaves the nearest object there, so every
+// interactive feature goes through the same lookup.
+#[derive(Default)]
+pub struct Picker {
+    items: Vec<Pickable>,
+    pass: Option<IdPass>, // Created on the first click, once a GL context exists
+}
+
+impl Picker {
+    // Forget last frame's objects
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    // Register an object for this frame
+    pub fn register(&mut self, object: SceneObject, center: Point3<f32>, radius: f32) {
+        self.items.push(Pickable {
+            object,
+            center,
+            radius,
+        });
+    }
+
+    // The object under the pixel `at` (from the top left) of a window of
+    // `size` pixels, seen through `camera`
+    pub fn pick(
+        &mut self,
+        camera: &dyn Camera,
+        at: (u32, u32),
+        size: (u32, u32),
+    ) -> Option<SceneObject> {
+        if self.items.is_empty() || at.0 >= size.0 || at.1 >= size.1 {
+            return None;
+        }
+        let pass = self.pass.get_or_insert_with(IdPass::new);
+        let id = pass.render(camera, &self.items, at, size);
+        let index = id.checked_sub(1)?;
+        self.items.get(index as usize).map(|item| item.object)
+    }
+}
+
+// Offscreen target and shader drawing every pickable as a sphere of its
+// bounding radius, colored by its index plus one (black is nothing)
+struct IdPass {
+    effect: Effect,
+    position: ShaderAttribute<Point3<f32>>,
+    center: ShaderUniform<Point3<f32>>,
+    radius: ShaderUniform<f32>,
+    id: ShaderUniform<Point3<f32>>,
+    proj: ShaderUniform<Matrix4<f32>>,
+    view: ShaderUniform<Matrix4<f32>>,
+    sphere: Mesh,
+    framebuffers: FramebufferManager,
+    target: RenderTarget,
+    size: (u32, u32),
+}
+
+impl IdPass {
+    fn new() -> IdPass {
+        let mut effect = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+        effect.use_program();
+        let (around, down) = PROXY_SUBDIVISIONS;
+        IdPass {
+            position: effect.get_attrib("position").unwrap(),
+            center: effect.get_uniform("center").unwrap(),
+            radius: effect.get_uniform("radius").unwrap(),
+            id: effect.get_uniform("id").unwrap(),
+            proj: effect.get_uniform("proj").unwrap(),
+            view: effect.get_uniform("view").unwrap(),
+            effect,
+            sphere: Mesh::from_trimesh(procedural::unit_sphere(around, down, false), false),
+            framebuffers: FramebufferManager::new(),
+            target: FramebufferManager::new_render_target(1, 1, false),
+            size: (1, 1),
+        }
+    }
+
+    // Draw the ID buffer and return the ID at pixel `at`
+    fn render(
+        &mut self,
+        camera: &dyn Camera,
+        items: &[Pickable],
+        at: (u32, u32),
+        size: (u32, u32),
+    ) -> u32 {
+        let ctxt = Context::get();
+        if self.size != size {
+            self.target.resize(size.0 as f32, size.1 as f32);
+            self.size = size;
+        }
+        self.framebuffers.select(&self.target);
+        ctxt.viewport(0, 0, size.0 as i32, size.1 as i32);
+        ctxt.clear_color(0.0, 0.0, 0.0, 0.0);
+        ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT);
+        ctxt.enable(Context::DEPTH_TEST);
+        ctxt.disable(Context::BLEND);
+        ctxt.disable(Context::CULL_FACE);
+
+        self.effect.use_program();
+        self.position.enable();
+        camera.upload(0, &mut self.proj, &mut self.view);
+        self.sphere.bind_coords(&mut self.position);
+        self.sphere.bind_faces();
+        let count = self.sphere.num_pts() as i32;
+        for (index, item) in items.iter().enumerate() {
+            self.center.upload(&item.center);
+            self.radius.upload(&(item.radius * 2.0)); // The unit sphere mesh has radius 0.5
+            self.id.upload(&encode(index as u32 + 1));
+            ctxt.draw_elements(Context::TRIANGLES, count, Context::UNSIGNED_SHORT, 0);
+        }
+        self.sphere.unbind();
+        self.position.disable();
+
+        // Rows count from the bottom in GL
+        let mut pixel = [0u8; 4];
+        ctxt.pixel_storei(Context::PACK_ALIGNMENT, 1);
+        ctxt.read_pixels(
+            at.0 as i32,
+            (size.1 - 1 - at.1) as i32,
+            1,
+            1,
+            Context::RGBA,
+            Some(&mut pixel),
+        );
+        self.framebuffers.select(&FramebufferManager::screen());
+        decode(pixel)
+    }
+}
+
+// An ID as a color, eight bits per channel
+fn encode(id: u32) -> Point3<f32> {
+    Point3::new(
+        (id >> 16 & 0xff) as f32 / 255.0,
+        (id >> 8 & 0xff) as f32 / 255.0,
+        (id & 0xff) as f32 / 255.0,
+    )
+}
+
+fn decode([r, g, b, _]: [u8; 4]) -> u32 {
+    (r as u32) << 16 | (g as u32) << 8 | b as u32
+}
+
+static VERTEX_SHADER: &str = "#version 100
+    attribute vec3 position;
+    uniform mat4 proj, view;
+    uniform vec3 center;
+    uniform float radius;
+
+    void main() {
+      gl_Position = proj * view * vec4(center + radius * position, 1.0);
+    }";
+
+static FRAGMENT_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform vec3 id;
+
+    void main() {
+      gl_FragColor = vec4(id, 1.0);
+    }";
@@ -0,0 +1,213 @@
+use kiss3d::nalgebra::{Matrix4, SymmetricEigen, Vector4};
+
+// 4D to 3D projections of chord-space points. Points use the motion-vector
+// layout [w, x, y, z], where w is the total-motion component.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    DropW,         // Ignore w (it still drives hue)
+    Perspective,   // Pinhole camera looking down the w axis
+    Stereographic, // Stereographic projection of each point's direction from the w pole
+    Pca,           // Top three principal components of the motion vectors
+}
+
+impl Projection {
+    pub fn from_name(name: &str) -> Option<Projection> {
+        match name {
+            "drop-w" => Some(Projection::DropW),
+            "perspective" => Some(Projection::Perspective),
+            "stereographic" => Some(Projection::Stereographic),
+            "pca" => Some(Projection::Pca),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Projection::DropW => "drop-w",
+            Projection::Perspective => "perspective",
+            Projection::Stereographic => "stereographic",
+            Projection::Pca => "pca",
+        }
+    }
+
+    // Next strategy when cycling at runtime
+    pub fn next(self) -> Projection {
+        match self {
+            Projection::DropW => Projection::Perspective,
+            Projection::Perspective => Projection::Stereographic,
+            Projection::Stereographic => Projection::Pca,
+            Projection::Pca => Projection::DropW,
+        }
+    }
+}
+
+// How far apart projected points land in the scene
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    Fixed(f32), // Scene units per 100 semitones of motion
+    Fit,        // Whatever fits the whole path into the grid
+}
+
+impl Scale {
+    // Parse "auto" or a positive number
+    pub fn from_spec(spec: &str) -> Option<Scale> {
+        match spec {
+            "auto" => Some(Scale::Fit),
+            _ => match spec.parse() {
+                Ok(scale) if scale > 0.0 => Some(Scale::Fixed(scale)),
+                _ => None,
+            },
+        }
+    }
+}
+
+// Speed of the yz rotation relative to xw, irrational so the double
+// rotation never quite repeats
+const YZ_RATIO: f32 = 0.618;
+
+// Double rotation: angle in the xw plane and angle * YZ_RATIO in the yz plane
+pub fn rotate(points: &[[f32; 4]], angle: f32) -> Vec<[f32; 4]> {
+    let (s1, c1) = angle.sin_cos();
+    let (s2, c2) = (angle * YZ_RATIO).sin_cos();
+    points
+        .iter()
+        .map(|&[w, x, y, z]| {
+            [
+                c1 * w - s1 * x,
+                s1 * w + c1 * x,
+                c2 * y - s2 * z,
+                s2 * y + c2 * z,
+            ]
+        })
+        .collect()
+}
+
+// Principal axes of the motion between consecutive points, strongest first,
+// with the share of the total variance the top three explain
+pub fn principal_axes(points: &[[f32; 4]]) -> ([Vector4<f32>; 3], f32) {
+    let motions: Vec<Vector4<f32>> = points
+        .windows(2)
+        .map(|w| Vector4::from(w[1]) - Vector4::from(w[0]))
+        .collect();
+    let n = motions.len().max(1) as f32;
+    let mean = motions.iter().fold(Vector4::zeros(), |acc, m| acc + m) / n;
+    let covariance = motions.iter().fold(Matrix4::zeros(), |acc, m| {
+        let d = m - mean;
+        acc + d * d.transpose()
+    }) / n;
+
+    let eigen = SymmetricEigen::new(covariance);
+    let mut order = [0, 1, 2, 3];
+    order.sort_by(|&a, &b| eigen.eigenvalues[b].total_cmp(&eigen.eigenvalues[a]));
+    let axes = [0, 1, 2].map(|i| eigen.eigenvectors.column(order[i]).into_owned());
+
+    let total: f32 = eigen.eigenvalues.iter().map(|v| v.max(0.0)).sum();
+    let top: f32 = order[..3]
+        .iter()
+        .map(|&i| eigen.eigenvalues[i].max(0.0))
+        .sum();
+    (axes, if total > 0.0 { top / total } else { 1.0 })
+}
+
+// Project a whole path, so the strategies can adapt to its extent
+pub fn project(points: &[[f32; 4]], projection: Projection) -> Vec<[f32; 3]> {
+    project_beside(points, points, projection)
+}
+
+// Project points lying near `path` exactly as projecting the path itself
+// would place them: the perspective eye and the principal axes come from
+// the path alone
+pub fn project_beside(
+    points: &[[f32; 4]],
+    path: &[[f32; 4]],
+    projection: Projection,
+) -> Vec<[f32; 3]> {
+    match projection {
+        Projection::DropW => points.iter().map(|p| [p[1], p[2], p[3]]).collect(),
+        Projection::Perspective => {
+            // Eye at twice the largest |w| so every path point stays in
+            // front of it; points beyond the eye are held just before it
+            let extent = path.iter().fold(1.0f32, |m, p| m.max(p[0].abs()));
+            let eye = 2.0 * extent;
+            points
+                .iter()
+                .map(|p| {
+                    let scale = eye / (eye - p[0]).max(0.1 * eye);
+                    [p[1] * scale, p[2] * scale, p[3] * scale]
+                })
+                .collect()
+        }
+        Projection::Stereographic => points
+            .iter()
+            .map(|p| {
+                // Project the direction on S³ from the +w pole, keep the radius
+                let norm = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2] + p[3] * p[3]).sqrt();
+                if norm < 1e-6 {
+                    return [0.0; 3];
+                }
+                let scale = 1.0 / (1.0 - p[0] / norm).max(0.1);
+                [p[1] * scale, p[2] * scale, p[3] * scale]
+            })
+            .collect(),
+        Projection::Pca => {
+            let (axes, _) = principal_axes(path);
+            points
+                .iter()
+                .map(|&p| {
+                    let p = Vector4::from(p);
+                    axes.map(|axis| axis.dot(&p))
+                })
+                .collect()
+        }
+    }
+}
+
+// Piece of a path segment near a hyperplane w = level, in x, y, z
+pub struct SlabPiece {
+    pub segment: usize,             // Index of the segment's first point
+    pub start: [f32; 3],            // Where the piece enters the slab
+    pub end: [f32; 3],              // Where it leaves
+    pub distance: f32,              // |w - level| at its middle, in half thicknesses
+    pub crossing: Option<[f32; 3]>, // Where the segment passes through the hyperplane
+}
+
+// The parts of a 4D path within `half` of the hyperplane w = level, for
+// looking at the path as a cross-section rather than a projection
+pub fn slab(points: &[[f32; 4]], level: f32, half: f32) -> Vec<SlabPiece> {
+    let at = |a: &[f32; 4], b: &[f32; 4], t: f32| [1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t);
+    let mut pieces = Vec::new();
+    for (segment, pair) in points.windows(2).enumerate() {
+        let (a, b) = (&pair[0], &pair[1]);
+        let (low, high) = (level - half, level + half);
+        let dw = b[0] - a[0];
+        // Parameter range of the segment inside the slab
+        let (t0, t1) = if dw.abs() < 1e-6 {
+            if a[0] < low || a[0] > high {
+                continue;
+            }
+            (0.0, 1.0)
+        } else {
+            let (ta, tb) = ((low - a[0]) / dw, (high - a[0]) / dw);
+            (ta.min(tb).max(0.0), ta.max(tb).min(1.0))
+        };
+        if t0 > t1 {
+            continue;
+        }
+        let middle = a[0] + dw * (t0 + t1) / 2.0;
+        let crossing = if dw.abs() < 1e-6 {
+            None
+        } else {
+            let t = (level - a[0]) / dw;
+            (0.0..=1.0).contains(&t).then(|| at(a, b, t))
+        };
+        pieces.push(SlabPiece {
+            segment,
+            start: at(a, b, t0),
+            end: at(a, b, t1),
+            distance: (middle - level).abs() / half,
+            crossing,
+        });
+    }
+    pieces
+}
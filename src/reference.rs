@@ -0,0 +1,89 @@
+use crate::json::{self, Value};
+use crate::transformation::MotionVec;
+use std::fs;
+use std::path::Path;
+
+// A static path to draw for comparison, in motion-vector units (x, y, z)
+#[derive(Debug, Clone)]
+pub struct ReferencePath {
+    pub label: String,
+    pub points: Vec<[f32; 3]>,
+}
+
+// Another piece's keyframe path, kept as motions so it can be smoothed,
+// rotated and projected along with the piece being played
+#[derive(Debug, Clone)]
+pub struct OverlayPath {
+    pub label: String,
+    pub origin: MotionVec,
+    pub motions: Vec<MotionVec>,
+}
+
+// Load a reference path. Accepts either a bare array of [x, y, z] points
+// or an object {"label": "...", "points": [[x, y, z], ...]} as written by
+// export(); the file name is used when no label is given.
+pub fn load(path: &Path) -> Result<ReferencePath, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)?;
+    let doc = json::parse(&text)?;
+
+    let (label, points) = match &doc {
+        Value::Array(_) => (None, &doc),
+        Value::Object(_) => (
+            doc.get("label").and_then(Value::as_str),
+            doc.get("points").ok_or("missing \"points\" array")?,
+        ),
+        _ => return Err("expected an array of points or an object with \"points\"".into()),
+    };
+
+    let mut out = Vec::new();
+    for (i, point) in points
+        .as_array()
+        .ok_or("\"points\" must be an array")?
+        .iter()
+        .enumerate()
+    {
+        let coords: Option<Vec<f32>> = point
+            .as_array()
+            .map(|c| c.iter().map(|v| v.as_f64().map(|n| n as f32)).collect())
+            .unwrap_or(None);
+        match coords.as_deref() {
+            Some(&[x, y, z]) => out.push([x, y, z]),
+            _ => return Err(format!("point {i} is not an [x, y, z] array of numbers").into()),
+        }
+    }
+
+    let label = label.map(str::to_string).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+    Ok(ReferencePath { label, points: out })
+}
+
+// Cumulative keyframe positions of a motion sequence from its first keyframe
+pub fn keyframe_points(origin: MotionVec, motions: &[MotionVec]) -> Vec<[f32; 3]> {
+    let mut points = vec![[origin[1], origin[2], origin[3]]];
+    for motion in motions {
+        let last = points[points.len() - 1];
+        points.push([
+            last[0] + motion[1],
+            last[1] + motion[2],
+            last[2] + motion[3],
+        ]);
+    }
+    points
+}
+
+// Write keyframe positions in the format load() reads back
+pub fn export(path: &Path, label: &str, points: &[[f32; 3]]) -> std::io::Result<()> {
+    let body: Vec<String> = points
+        .iter()
+        .map(|p| format!("    [{}, {}, {}]", p[0], p[1], p[2]))
+        .collect();
+    let text = format!(
+        "{{\n  \"label\": {},\n  \"points\": [\n{}\n  ]\n}}\n",
+        json::quote(label),
+        body.join(",\n")
+    );
+    fs::write(path, text)
+}
@@ -1,18 +1,199 @@
-pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (f32, f32, f32) {
-    let h = h.fract() * 6.0;
-    let i = h.floor() as i32;
-    let f = h - i as f32;
-    let p = v * (1.0 - s);
-    let q = v * (1.0 - f * s);
-    let t = v * (1.0 - (1.0 - f) * s);
-
-    match i % 6 {
-        0 => (v, t, p),
-        1 => (q, v, p),
-        2 => (p, v, t),
-        3 => (p, q, v),
-        4 => (t, p, v),
-        5 => (v, p, q),
-        _ => (0.0, 0.0, 0.0), // should never hit
+// Red, green and blue in 0..1
+pub type Rgb = (f32, f32, f32);
+
+// Named per-voice palettes, in voice order (usually soprano, alto, tenor, bass)
+pub const VOICE_PALETTES: [(&str, [Rgb; 4]); 4] = [
+    // Okabe-Ito colors, distinguishable with common color blindness
+    (
+        "distinct",
+        [
+            (0.84, 0.37, 0.0),
+            (0.94, 0.89, 0.26),
+            (0.0, 0.62, 0.45),
+            (0.34, 0.71, 0.91),
+        ],
+    ),
+    (
+        "primary",
+        [
+            (0.9, 0.2, 0.2),
+            (0.95, 0.8, 0.2),
+            (0.2, 0.8, 0.3),
+            (0.25, 0.4, 0.95),
+        ],
+    ),
+    (
+        "pastel",
+        [
+            (0.98, 0.6, 0.6),
+            (0.98, 0.9, 0.6),
+            (0.6, 0.9, 0.7),
+            (0.6, 0.7, 0.98),
+        ],
+    ),
+    // Okabe-Ito hues a step apart in lightness from the top voice down,
+    // so voices stay apart under deuteranopia and protanopia simulation
+    (
+        "colorblind",
+        [
+            (0.94, 0.89, 0.26),
+            (0.34, 0.71, 0.91),
+            (0.84, 0.37, 0.0),
+            (0.0, 0.3, 0.6),
+        ],
+    ),
+];
+
+pub fn voice_palette(name: &str) -> Option<[Rgb; 4]> {
+    VOICE_PALETTES
+        .iter()
+        .find(|(palette, _)| *palette == name)
+        .map(|&(_, colors)| colors)
+}
+
+// What the hue of the sphere and trail encodes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coloring {
+    Motion,  // Total voice motion of the step, cycling with the color scale
+    Quality, // Quality of the chord arrived at
+}
+
+impl Coloring {
+    pub fn from_name(name: &str) -> Option<Coloring> {
+        match name {
+            "motion" => Some(Coloring::Motion),
+            "quality" => Some(Coloring::Quality),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Coloring::Motion => "motion",
+            Coloring::Quality => "quality",
+        }
+    }
+
+    // Next coloring when cycling at runtime
+    pub fn next(self) -> Coloring {
+        match self {
+            Coloring::Motion => Coloring::Quality,
+            Coloring::Quality => Coloring::Motion,
+        }
     }
 }
+
+// Colors a hue value (0..1) is mapped through, as evenly spaced stops
+// blended linearly; the value wraps, so palettes that do not end where
+// they start jump back at every cycle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette(&'static [Rgb]);
+
+impl Palette {
+    pub fn at(self, value: f32) -> Rgb {
+        let stops = self.0;
+        let position = value.rem_euclid(1.0) * (stops.len() - 1) as f32;
+        let i = (position.floor() as usize).min(stops.len() - 2);
+        let f = position - i as f32;
+        let (a, b) = (stops[i], stops[i + 1]);
+        (
+            a.0 + (b.0 - a.0) * f,
+            a.1 + (b.1 - a.1) * f,
+            a.2 + (b.2 - a.2) * f,
+        )
+    }
+
+    // The palette color blended toward white, as lowering the saturation
+    // does in HSV
+    pub fn soft(self, value: f32, saturation: f32) -> Rgb {
+        let (r, g, b) = self.at(value);
+        let soften = |c: f32| 1.0 - saturation * (1.0 - c);
+        (soften(r), soften(g), soften(b))
+    }
+}
+
+// Named hue palettes selectable with --palette, first is the default
+pub const PALETTES: [(&str, Palette); 6] = [
+    // The full HSV hue circle
+    (
+        "rainbow",
+        Palette(&[
+            (1.0, 0.0, 0.0),
+            (1.0, 1.0, 0.0),
+            (0.0, 1.0, 0.0),
+            (0.0, 1.0, 1.0),
+            (0.0, 0.0, 1.0),
+            (1.0, 0.0, 1.0),
+            (1.0, 0.0, 0.0),
+        ]),
+    ),
+    // Perceptually uniform, dark violet to yellow
+    (
+        "viridis",
+        Palette(&[
+            (0.267, 0.004, 0.329),
+            (0.282, 0.157, 0.471),
+            (0.243, 0.29, 0.537),
+            (0.192, 0.408, 0.557),
+            (0.149, 0.51, 0.557),
+            (0.122, 0.62, 0.537),
+            (0.208, 0.718, 0.475),
+            (0.427, 0.804, 0.349),
+            (0.706, 0.871, 0.173),
+            (0.992, 0.906, 0.145),
+        ]),
+    ),
+    // Perceptually uniform, deep blue through magenta to yellow
+    (
+        "plasma",
+        Palette(&[
+            (0.051, 0.031, 0.529),
+            (0.255, 0.016, 0.616),
+            (0.416, 0.0, 0.659),
+            (0.561, 0.051, 0.643),
+            (0.694, 0.165, 0.565),
+            (0.8, 0.278, 0.471),
+            (0.882, 0.392, 0.384),
+            (0.949, 0.518, 0.294),
+            (0.988, 0.651, 0.212),
+            (0.988, 0.808, 0.145),
+            (0.941, 0.976, 0.129),
+        ]),
+    ),
+    // Two tones for harmonic tension: calm blue for small motion, through
+    // pale gray, to tense red for large
+    (
+        "tension",
+        Palette(&[(0.23, 0.42, 0.9), (0.86, 0.86, 0.88), (0.9, 0.22, 0.16)]),
+    ),
+    // The same idea in teal and orange, kept bright so it reads on dark
+    // backgrounds
+    (
+        "tension-bright",
+        Palette(&[(0.1, 0.75, 0.75), (0.95, 0.95, 0.85), (1.0, 0.55, 0.1)]),
+    ),
+    // Blue to yellow with lightness rising steadily, which looks nearly
+    // the same with red-green color blindness
+    (
+        "cividis",
+        Palette(&[
+            (0.0, 0.133, 0.306),
+            (0.071, 0.208, 0.439),
+            (0.231, 0.286, 0.424),
+            (0.341, 0.365, 0.427),
+            (0.439, 0.443, 0.451),
+            (0.541, 0.529, 0.475),
+            (0.651, 0.616, 0.459),
+            (0.769, 0.71, 0.424),
+            (0.894, 0.812, 0.357),
+            (0.996, 0.91, 0.22),
+        ]),
+    ),
+];
+
+pub fn palette(name: &str) -> Option<Palette> {
+    PALETTES
+        .iter()
+        .find(|(palette, _)| *palette == name)
+        .map(|&(_, palette)| palette)
+}
@@ -0,0 +1,75 @@
+use crate::hud::planar;
+use crate::rgba::Rgb;
+use kiss3d::nalgebra::Point3;
+use kiss3d::window::Window;
+
+pub const ROLL_HEIGHT: f32 = 110.0; // Pixels, including the margin above the window edge
+const ROLL_MARGIN: f32 = 20.0;
+const ROLL_SECONDS: f32 = 10.0; // Piece time across the strip
+const PLAYHEAD_AT: f32 = 0.25; // Playhead position across the strip
+const LAST_CHORD: f32 = 0.5; // Seconds the final chord is drawn for
+const FRAME_COLOR: (f32, f32, f32) = (0.3, 0.3, 0.4);
+
+// Four-voice piano roll along the bottom of the window, scrolling under a
+// fixed playhead at piece time `now`; `starts` holds the piece time of
+// every keyframe. `lift` raises it clear of anything below.
+pub fn draw(
+    window: &mut Window,
+    chords: &[[i32; 4]],
+    starts: &[f32],
+    now: f32,
+    colors: &[Rgb; 4],
+    lift: f32,
+) {
+    let width = window.width() as f32;
+    let bottom = window.height() as f32 - ROLL_MARGIN - lift;
+    let top = bottom - (ROLL_HEIGHT - ROLL_MARGIN);
+    let (left, right) = (ROLL_MARGIN, width - ROLL_MARGIN);
+
+    let (r, g, b) = FRAME_COLOR;
+    let frame = Point3::new(r, g, b);
+    let corners = [(left, top), (right, top), (right, bottom), (left, bottom)];
+    for i in 0..4 {
+        let (ax, ay) = corners[i];
+        let (bx, by) = corners[(i + 1) % 4];
+        window.draw_planar_line(&planar(window, ax, ay), &planar(window, bx, by), &frame);
+    }
+
+    // Lowest and highest pitch of the piece span the strip, one pitch spare
+    let low = chords.iter().flatten().copied().min().unwrap_or(60) - 1;
+    let high = chords.iter().flatten().copied().max().unwrap_or(60) + 1;
+    let pitch_y = |pitch: i32| bottom - (pitch - low) as f32 / (high - low) as f32 * (bottom - top);
+
+    let start = now - PLAYHEAD_AT * ROLL_SECONDS;
+    let time_x = |t: f32| left + (t - start) / ROLL_SECONDS * (right - left);
+    for (k, chord) in chords.iter().enumerate() {
+        let begin = starts.get(k).copied().unwrap_or(now);
+        let end = starts.get(k + 1).copied().unwrap_or(begin + LAST_CHORD);
+        if end < start || begin > start + ROLL_SECONDS {
+            continue;
+        }
+        // A pixel's gap at each keyframe keeps repeated notes apart
+        let x1 = time_x(begin).max(left) + 1.0;
+        let x2 = time_x(end).min(right);
+        if x2 <= x1 {
+            continue;
+        }
+        for (&pitch, &(r, g, b)) in chord.iter().zip(colors) {
+            let y = pitch_y(pitch);
+            for dy in [-1.0, 0.0, 1.0] {
+                window.draw_planar_line(
+                    &planar(window, x1, y + dy),
+                    &planar(window, x2, y + dy),
+                    &Point3::new(r, g, b),
+                );
+            }
+        }
+    }
+
+    let playhead = time_x(now);
+    window.draw_planar_line(
+        &planar(window, playhead, top),
+        &planar(window, playhead, bottom),
+        &Point3::new(1.0, 1.0, 1.0),
+    );
+}
@@ -0,0 +1,323 @@
+use crate::rgba::Rgb;
+use crate::theme::Intensity;
+use kiss3d::camera::Camera;
+use kiss3d::context::Context;
+use kiss3d::light::Light;
+use kiss3d::nalgebra::{Isometry3, Matrix3, Matrix4, Point2, Point3, Vector3};
+use kiss3d::resource::{Effect, Material, MaterialManager, Mesh, ShaderAttribute, ShaderUniform};
+use kiss3d::scene::ObjectData;
+use kiss3d::window::Window;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+const SPHERE_LIGHT_RANGE: f32 = 150.0; // Distance at which the sphere's light has halved
+const FILL_FROM: [f32; 3] = [-0.4, -1.0, 0.3]; // Direction towards the fill light (world)
+
+type SharedMaterial = Rc<RefCell<Box<dyn Material + 'static>>>;
+
+// Surface of one object beyond its color, attached as its user data
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Finish {
+    pub emissive: f32, // Share of the object's own color it gives off, unlit
+    pub opacity: f32,  // Below 1 lets what is behind show through
+}
+
+impl Default for Finish {
+    fn default() -> Self {
+        Finish {
+            emissive: 0.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+// Brightness the sphere's light leaves at a distance from its centre, as
+// a share of its intensity
+pub fn sphere_light_falloff(distance: f32) -> f32 {
+    let d = distance / SPHERE_LIGHT_RANGE;
+    1.0 / (1.0 + d * d)
+}
+
+// kiss3d's object shading with a fill light and a point light at the
+// sphere added to its one light, each with its own intensity
+struct LitMaterial {
+    effect: Effect,
+    position: ShaderAttribute<Point3<f32>>,
+    normal: ShaderAttribute<Vector3<f32>>,
+    tex_coord: ShaderAttribute<Point2<f32>>,
+    light: ShaderUniform<Point3<f32>>,
+    color: ShaderUniform<Point3<f32>>,
+    transform: ShaderUniform<Matrix4<f32>>,
+    scale: ShaderUniform<Matrix3<f32>>,
+    ntransform: ShaderUniform<Matrix3<f32>>,
+    proj: ShaderUniform<Matrix4<f32>>,
+    view: ShaderUniform<Matrix4<f32>>,
+    fill_from: ShaderUniform<Vector3<f32>>,
+    sphere_position: ShaderUniform<Point3<f32>>,
+    sphere_color: ShaderUniform<Vector3<f32>>,
+    key: ShaderUniform<f32>,
+    fill: ShaderUniform<f32>,
+    sphere: ShaderUniform<f32>,
+    range: ShaderUniform<f32>,
+    emissive: ShaderUniform<f32>,
+    opacity: ShaderUniform<f32>,
+    intensity: Intensity,
+    glow: Rc<Cell<(Point3<f32>, Rgb)>>, // Where the sphere is and its color
+}
+
+impl Material for LitMaterial {
+    fn render(
+        &mut self,
+        pass: usize,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        camera: &mut dyn Camera,
+        light: &Light,
+        data: &ObjectData,
+        mesh: &mut Mesh,
+    ) {
+        let ctxt = Context::get();
+        self.effect.use_program();
+        self.position.enable();
+        self.normal.enable();
+        self.tex_coord.enable();
+
+        camera.upload(pass, &mut self.proj, &mut self.view);
+        self.light.upload(&match *light {
+            Light::Absolute(position) => position,
+            Light::StickToCamera => camera.eye(),
+        });
+        let (at, (r, g, b)) = self.glow.get();
+        self.sphere_position.upload(&at);
+        self.sphere_color.upload(&Vector3::new(r, g, b));
+        self.fill_from.upload(&Vector3::from(FILL_FROM).normalize());
+        self.key.upload(&self.intensity.key);
+        self.fill.upload(&self.intensity.fill);
+        self.sphere.upload(&self.intensity.sphere);
+        self.range.upload(&SPHERE_LIGHT_RANGE);
+        let finish = data
+            .user_data()
+            .downcast_ref::<Finish>()
+            .copied()
+            .unwrap_or_default();
+        self.emissive.upload(&finish.emissive);
+        self.opacity.upload(&finish.opacity);
+
+        // Translucent objects blend over the scene without hiding what is
+        // drawn after them, such as the trail lines behind
+        let translucent = finish.opacity < 1.0;
+        if translucent {
+            ctxt.enable(Context::BLEND);
+            ctxt.blend_func_separate(
+                Context::SRC_ALPHA,
+                Context::ONE_MINUS_SRC_ALPHA,
+                Context::ONE,
+                Context::ONE_MINUS_SRC_ALPHA,
+            );
+            ctxt.disable(Context::DEPTH_TEST);
+        }
+
+        self.transform.upload(&transform.to_homogeneous());
+        self.ntransform
+            .upload(&transform.rotation.to_rotation_matrix().into_inner());
+        self.scale.upload(&Matrix3::from_diagonal(scale));
+
+        mesh.bind(&mut self.position, &mut self.normal, &mut self.tex_coord);
+        ctxt.active_texture(Context::TEXTURE0);
+        ctxt.bind_texture(Context::TEXTURE_2D, Some(data.texture().as_ref()));
+
+        // Surface, wireframe and points, as the default material draws them
+        let count = mesh.num_pts() as i32;
+        if data.surface_rendering_active() {
+            self.color.upload(data.color());
+            if data.backface_culling_enabled() {
+                ctxt.enable(Context::CULL_FACE);
+            } else {
+                ctxt.disable(Context::CULL_FACE);
+            }
+            let _ = ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::FILL);
+            ctxt.draw_elements(Context::TRIANGLES, count, Context::UNSIGNED_SHORT, 0);
+        }
+        if data.lines_width() != 0.0 {
+            self.color
+                .upload(data.lines_color().unwrap_or(data.color()));
+            ctxt.disable(Context::CULL_FACE);
+            ctxt.line_width(data.lines_width());
+            if ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::LINE) {
+                ctxt.draw_elements(Context::TRIANGLES, count, Context::UNSIGNED_SHORT, 0);
+            } else {
+                mesh.bind_edges();
+                ctxt.draw_elements(Context::LINES, count * 2, Context::UNSIGNED_SHORT, 0);
+            }
+            ctxt.line_width(1.0);
+        }
+        if data.points_size() != 0.0 {
+            self.color.upload(data.color());
+            ctxt.disable(Context::CULL_FACE);
+            ctxt.point_size(data.points_size());
+            if ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::POINT) {
+                ctxt.draw_elements(Context::TRIANGLES, count, Context::UNSIGNED_SHORT, 0);
+            } else {
+                ctxt.draw_elements(Context::POINTS, count, Context::UNSIGNED_SHORT, 0);
+            }
+            ctxt.point_size(1.0);
+        }
+
+        if translucent {
+            ctxt.enable(Context::DEPTH_TEST);
+            ctxt.disable(Context::BLEND);
+        }
+        mesh.unbind();
+        self.position.disable();
+        self.normal.disable();
+        self.tex_coord.disable();
+    }
+}
+
+// The scene's lights, shared by every lit object
+pub struct Shading {
+    material: SharedMaterial,
+    default: SharedMaterial,
+    glow: Rc<Cell<(Point3<f32>, Rgb)>>,
+}
+
+impl Shading {
+    pub fn new(intensity: Intensity) -> Shading {
+        let mut effect = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+        effect.use_program();
+        let glow = Rc::new(Cell::new((Point3::origin(), (1.0, 1.0, 1.0))));
+        let material = LitMaterial {
+            position: effect.get_attrib("position").unwrap(),
+            normal: effect.get_attrib("normal").unwrap(),
+            tex_coord: effect.get_attrib("tex_coord").unwrap(),
+            light: effect.get_uniform("light_position").unwrap(),
+            color: effect.get_uniform("color").unwrap(),
+            transform: effect.get_uniform("transform").unwrap(),
+            scale: effect.get_uniform("scale").unwrap(),
+            ntransform: effect.get_uniform("ntransform").unwrap(),
+            proj: effect.get_uniform("proj").unwrap(),
+            view: effect.get_uniform("view").unwrap(),
+            fill_from: effect.get_uniform("fill_from").unwrap(),
+            sphere_position: effect.get_uniform("sphere_position").unwrap(),
+            sphere_color: effect.get_uniform("sphere_color").unwrap(),
+            key: effect.get_uniform("key").unwrap(),
+            fill: effect.get_uniform("fill").unwrap(),
+            sphere: effect.get_uniform("sphere").unwrap(),
+            range: effect.get_uniform("range").unwrap(),
+            emissive: effect.get_uniform("emissive").unwrap(),
+            opacity: effect.get_uniform("opacity").unwrap(),
+            effect,
+            intensity,
+            glow: glow.clone(),
+        };
+        let material: Box<dyn Material> = Box::new(material);
+        Shading {
+            material: Rc::new(RefCell::new(material)),
+            default: MaterialManager::get_global_manager(|manager| manager.get_default()),
+            glow,
+        }
+    }
+
+    // Move the sphere's light along with it, in the sphere's current color
+    pub fn follow(&self, position: Point3<f32>, color: Rgb) {
+        self.glow.set((position, color));
+    }
+
+    // Light every object still drawn with kiss3d's default material, so
+    // nodes added since the last call are lit too; objects with their own
+    // material, like the sky, are left alone
+    pub fn apply(&self, window: &mut Window) {
+        window
+            .scene_mut()
+            .data_mut()
+            .apply_to_objects_mut(&mut |object| {
+                if Rc::ptr_eq(&object.material(), &self.default) {
+                    object.set_material(self.material.clone());
+                }
+            });
+    }
+}
+
+static VERTEX_SHADER: &str = "#version 100
+    attribute vec3 position;
+    attribute vec2 tex_coord;
+    attribute vec3 normal;
+
+    uniform mat3 ntransform, scale;
+    uniform mat4 proj, view, transform;
+    uniform vec3 light_position;
+    uniform vec3 sphere_position;
+    uniform vec3 fill_from;
+
+    varying vec3 local_light_position;
+    varying vec3 local_sphere_position;
+    varying vec3 local_fill_from;
+    varying vec2 tex_coord_v;
+    varying vec3 normalInterp;
+    varying vec3 vertPos;
+
+    void main() {
+      gl_Position = proj * view * transform * vec4(scale * position, 1.0);
+      vec4 vertPos4 = view * transform * vec4(scale * position, 1.0);
+      vertPos = vec3(vertPos4) / vertPos4.w;
+      normalInterp = mat3(view) * ntransform * normal;
+      tex_coord_v = tex_coord;
+      local_light_position = (view * vec4(light_position, 1.0)).xyz;
+      local_sphere_position = (view * vec4(sphere_position, 1.0)).xyz;
+      local_fill_from = mat3(view) * fill_from;
+    }";
+
+// kiss3d's Phong-like shading scaled by the key intensity, plus diffuse
+// fill and sphere lights; the sphere's light fades with distance, and an
+// emissive finish blends towards the unlit color
+static FRAGMENT_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    varying vec3 local_light_position;
+    varying vec3 local_sphere_position;
+    varying vec3 local_fill_from;
+    varying vec2 tex_coord_v;
+    varying vec3 normalInterp;
+    varying vec3 vertPos;
+
+    uniform vec3 color;
+    uniform vec3 sphere_color;
+    uniform sampler2D tex;
+    uniform float key;
+    uniform float fill;
+    uniform float sphere;
+    uniform float range;
+    uniform float emissive;
+    uniform float opacity;
+    const vec3 specColor = vec3(0.4, 0.4, 0.4);
+
+    void main() {
+      vec3 normal = normalize(normalInterp);
+      vec3 lightDir = normalize(local_light_position - vertPos);
+
+      float lambertian = max(dot(lightDir, normal), 0.0);
+      float specular = 0.0;
+      if (lambertian > 0.0) {
+        vec3 viewDir = normalize(-vertPos);
+        vec3 halfDir = normalize(lightDir + viewDir);
+        float specAngle = max(dot(halfDir, normal), 0.0);
+        specular = pow(specAngle, 30.0);
+      }
+
+      float filled = max(dot(normalize(local_fill_from), normal), 0.0);
+
+      vec3 toSphere = local_sphere_position - vertPos;
+      float d = length(toSphere) / range;
+      float glow = max(dot(toSphere / max(length(toSphere), 0.001), normal), 0.0) / (1.0 + d * d);
+
+      vec4 tex_color = texture2D(tex, tex_coord_v);
+      vec3 shade = color / 3.0
+        + key * (lambertian * color / 3.0 + specular * specColor / 3.0)
+        + fill * filled * color / 3.0
+        + sphere * glow * sphere_color * color;
+      gl_FragColor = tex_color * vec4(mix(shade, color, emissive), opacity);
+    }";
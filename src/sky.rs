@@ -0,0 +1,129 @@
+use crate::theme::Sky;
+use kiss3d::camera::Camera;
+use kiss3d::context::Context;
+use kiss3d::light::Light;
+use kiss3d::nalgebra::{Isometry3, Matrix3, Matrix4, Point3, Vector3};
+use kiss3d::resource::{Effect, Material, Mesh, ShaderAttribute, ShaderUniform};
+use kiss3d::scene::{ObjectData, SceneNode};
+use kiss3d::window::Window;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const SKY_RADIUS: f32 = 10.0; // Any size past the near plane will do, it is drawn behind everything
+
+// Gradient dome drawn without depth, so everything added after it covers it
+struct SkyMaterial {
+    shader: Effect,
+    position: ShaderAttribute<Point3<f32>>,
+    proj: ShaderUniform<Matrix4<f32>>,
+    view: ShaderUniform<Matrix4<f32>>,
+    transform: ShaderUniform<Matrix4<f32>>,
+    scale: ShaderUniform<Matrix3<f32>>,
+    zenith: ShaderUniform<Vector3<f32>>,
+    horizon: ShaderUniform<Vector3<f32>>,
+    ground: ShaderUniform<Vector3<f32>>,
+    colors: Sky,
+}
+
+impl Material for SkyMaterial {
+    fn render(
+        &mut self,
+        pass: usize,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        camera: &mut dyn Camera,
+        _: &Light,
+        _: &ObjectData,
+        mesh: &mut Mesh,
+    ) {
+        let ctxt = Context::get();
+        ctxt.disable(Context::CULL_FACE);
+        ctxt.disable(Context::DEPTH_TEST);
+
+        self.shader.use_program();
+        self.position.enable();
+        camera.upload(pass, &mut self.view, &mut self.proj);
+        self.transform.upload(&transform.to_homogeneous());
+        self.scale.upload(&Matrix3::from_diagonal(scale));
+        let color = |(r, g, b): (f32, f32, f32)| Vector3::new(r, g, b);
+        self.zenith.upload(&color(self.colors.zenith));
+        self.horizon.upload(&color(self.colors.horizon));
+        self.ground.upload(&color(self.colors.ground));
+
+        mesh.bind_coords(&mut self.position);
+        mesh.bind_faces();
+        ctxt.draw_elements(
+            Context::TRIANGLES,
+            mesh.num_pts() as i32,
+            Context::UNSIGNED_INT,
+            0,
+        );
+        mesh.unbind();
+        self.position.disable();
+
+        ctxt.enable(Context::DEPTH_TEST);
+    }
+}
+
+// Add the sky; it must be the first node so it is drawn before the scene,
+// and it has to be kept centred on the camera with follow()
+pub fn add_sky(window: &mut Window, colors: Sky) -> SceneNode {
+    let mut shader = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+    shader.use_program();
+    let material = SkyMaterial {
+        position: shader.get_attrib("position").unwrap(),
+        proj: shader.get_uniform("proj").unwrap(),
+        view: shader.get_uniform("view").unwrap(),
+        transform: shader.get_uniform("transform").unwrap(),
+        scale: shader.get_uniform("scale").unwrap(),
+        zenith: shader.get_uniform("zenith").unwrap(),
+        horizon: shader.get_uniform("horizon").unwrap(),
+        ground: shader.get_uniform("ground").unwrap(),
+        shader,
+        colors,
+    };
+
+    let mut node = window.add_sphere(SKY_RADIUS);
+    let material: Box<dyn Material> = Box::new(material);
+    node.set_material(Rc::new(RefCell::new(material)));
+    node
+}
+
+// Keep the sky around the eye so it never gets closer or farther
+pub fn follow(node: &mut SceneNode, camera: &dyn Camera) {
+    node.set_local_translation(camera.eye().coords.into());
+}
+
+static VERTEX_SHADER: &str = "#version 100
+    attribute vec3 position;
+    uniform mat4   proj;
+    uniform mat4   view;
+    uniform mat4   transform;
+    uniform mat3   scale;
+    varying vec3   direction;
+
+    void main(void) {
+      direction   = position;
+      gl_Position = proj * view * transform * mat4(scale) * vec4(position, 1.0);
+    }";
+
+// Ground color below the horizon, blending up to the zenith color overhead
+static FRAGMENT_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform vec3 zenith;
+    uniform vec3 horizon;
+    uniform vec3 ground;
+    varying vec3 direction;
+
+    void main(void) {
+      float height = normalize(direction).y;
+      vec3 color = height > 0.0
+        ? mix(horizon, zenith, sqrt(height))
+        : mix(horizon, ground, sqrt(-height));
+      gl_FragColor = vec4(color, 1.0);
+    }";
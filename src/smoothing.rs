@@ -0,0 +1,61 @@
+// Optional low-pass filters for motion vectors, to calm the jitter from
+// fast passing tones
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Smoothing {
+    MovingAverage(usize), // Centred window of this many motions
+    Exponential(f32),     // Weight of the newest motion (0-1]
+}
+
+impl Smoothing {
+    // Parse "average:<window>" or "exp:<alpha>"
+    pub fn from_spec(spec: &str) -> Option<Smoothing> {
+        let (kind, value) = spec.split_once(':')?;
+        match kind {
+            "average" => match value.parse() {
+                Ok(window) if window >= 1 => Some(Smoothing::MovingAverage(window)),
+                _ => None,
+            },
+            "exp" => match value.parse() {
+                Ok(alpha) if alpha > 0.0 && alpha <= 1.0 => Some(Smoothing::Exponential(alpha)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+// Filter every component of the motion vectors independently
+pub fn apply(motions: &[[f32; 4]], smoothing: Smoothing) -> Vec<[f32; 4]> {
+    match smoothing {
+        Smoothing::MovingAverage(window) => {
+            let before = (window - 1) / 2;
+            let after = window - 1 - before;
+            (0..motions.len())
+                .map(|i| {
+                    let range =
+                        &motions[i.saturating_sub(before)..(i + after + 1).min(motions.len())];
+                    let mut sum = [0.0; 4];
+                    for m in range {
+                        for j in 0..4 {
+                            sum[j] += m[j];
+                        }
+                    }
+                    sum.map(|v| v / range.len() as f32)
+                })
+                .collect()
+        }
+        Smoothing::Exponential(alpha) => {
+            let mut state = [0.0; 4];
+            motions
+                .iter()
+                .map(|m| {
+                    for j in 0..4 {
+                        state[j] = alpha * m[j] + (1.0 - alpha) * state[j];
+                    }
+                    state
+                })
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+const CHANNEL: u8 = 0; // MIDI channel the chords are played on
+const VELOCITY: u8 = 80; // Loudness of every note
+const ALL_NOTES_OFF: u8 = 123; // Channel mode controller silencing the channel
+
+// Sounds each keyframe's chord on a raw MIDI port, such as an ALSA
+// /dev/snd/midiC*D* device or a FIFO read by a synthesizer; voices that
+// move are released as the next chord starts, all of them on a pause
+pub struct MidiOut {
+    port: File,
+    sounding: Option<[i32; 4]>, // Chord being held
+}
+
+impl MidiOut {
+    pub fn open(path: &Path) -> Result<MidiOut, String> {
+        let port = OpenOptions::new()
+            .write(true)
+            .open(path)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(MidiOut {
+            port,
+            sounding: None,
+        })
+    }
+
+    // Hold the chord of `keyframe`, or nothing. Only voices that change
+    // are released or struck, so a chord repeated over several keyframes,
+    // or reached again by a seek, keeps sounding
+    pub fn follow(&mut self, keyframe: Option<usize>, chords: &[[i32; 4]]) -> Result<(), String> {
+        let chord = keyframe.and_then(|k| chords.get(k).copied());
+        if chord == self.sounding {
+            return Ok(());
+        }
+        let held = self.sounding.map(notes).unwrap_or_default();
+        let next = chord.map(notes).unwrap_or_default();
+        let mut bytes = Vec::new();
+        for &pitch in held.iter().filter(|pitch| !next.contains(pitch)) {
+            bytes.extend([0x80 | CHANNEL, pitch, 0]);
+        }
+        for &pitch in next.iter().filter(|pitch| !held.contains(pitch)) {
+            bytes.extend([0x90 | CHANNEL, pitch, VELOCITY]);
+        }
+        self.sounding = chord;
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        self.port
+            .write_all(&bytes)
+            .and_then(|()| self.port.flush())
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl Drop for MidiOut {
+    // Leave nothing sounding once the window closes
+    fn drop(&mut self) {
+        let _ = self.follow(None, &[]);
+        let _ = self.port.write_all(&[0xB0 | CHANNEL, ALL_NOTES_OFF, 0]);
+    }
+}
+
+// Distinct voice pitches that fall in MIDI's note range; a doubled voice
+// is one note
+fn notes(chord: [i32; 4]) -> Vec<u8> {
+    let mut notes: Vec<u8> = chord
+        .into_iter()
+        .filter_map(|pitch| u8::try_from(pitch).ok().filter(|&p| p < 128))
+        .collect();
+    notes.sort_unstable();
+    notes.dedup();
+    notes
+}
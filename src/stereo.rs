@@ -0,0 +1,240 @@
+use kiss3d::camera::Camera;
+use kiss3d::context::Context;
+use kiss3d::event::WindowEvent;
+use kiss3d::nalgebra::{Isometry3, Matrix4, Point3, Translation3, Vector2};
+use kiss3d::post_processing::PostProcessingEffect;
+use kiss3d::resource::{
+    AllocationType, BufferType, Effect, FramebufferManager, GPUVec, RenderTarget, ShaderAttribute,
+    ShaderUniform,
+};
+use kiss3d::window::Canvas;
+
+const EYE_SEPARATION: f32 = 1.0 / 30.0; // Distance between the eyes, per unit of focus distance
+const MIN_FOCUS: f32 = 50.0; // Closest the zero-parallax plane comes to the eye
+
+// The scene camera seen from one eye: shifted sideways by half the eye
+// separation, with the projection skewed back so that whatever lies at the
+// focus distance lands in the same place for both eyes
+pub struct Eye<'a> {
+    camera: &'a mut dyn Camera,
+    offset: f32, // Sideways shift along the view's x axis, negative for the left eye
+    focus: f32,  // Distance of the zero-parallax plane
+}
+
+impl<'a> Eye<'a> {
+    // `left` picks the eye, `focus` is the scene point to keep at screen depth
+    pub fn new(camera: &'a mut dyn Camera, left: bool, focus: Point3<f32>) -> Eye<'a> {
+        let focus = (camera.eye() - focus).norm().max(MIN_FOCUS);
+        let half = focus * EYE_SEPARATION / 2.0;
+        Eye {
+            camera,
+            offset: if left { -half } else { half },
+            focus,
+        }
+    }
+
+    fn view(&self) -> Isometry3<f32> {
+        Translation3::new(-self.offset, 0.0, 0.0) * self.camera.view_transform()
+    }
+
+    fn projection(&self) -> Matrix4<f32> {
+        let projection =
+            self.camera.transformation() * self.camera.view_transform().inverse().to_homogeneous();
+        let mut skew = Matrix4::identity();
+        skew[(0, 3)] = projection[(0, 0)] * self.offset / self.focus;
+        skew * projection
+    }
+}
+
+impl Camera for Eye<'_> {
+    fn handle_event(&mut self, canvas: &Canvas, event: &WindowEvent) {
+        self.camera.handle_event(canvas, event);
+    }
+
+    fn eye(&self) -> Point3<f32> {
+        self.view().inverse() * Point3::origin()
+    }
+
+    fn view_transform(&self) -> Isometry3<f32> {
+        self.view()
+    }
+
+    fn transformation(&self) -> Matrix4<f32> {
+        self.projection() * self.view().to_homogeneous()
+    }
+
+    fn inverse_transformation(&self) -> Matrix4<f32> {
+        self.transformation()
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity)
+    }
+
+    fn clip_planes(&self) -> (f32, f32) {
+        self.camera.clip_planes()
+    }
+
+    fn update(&mut self, canvas: &Canvas) {
+        self.camera.update(canvas);
+    }
+
+    fn upload(
+        &self,
+        _: usize,
+        proj: &mut ShaderUniform<Matrix4<f32>>,
+        view: &mut ShaderUniform<Matrix4<f32>>,
+    ) {
+        proj.upload(&self.projection());
+        view.upload(&self.view().to_homogeneous());
+    }
+}
+
+// Red/cyan anaglyph from alternate frames: each frame renders one eye,
+// which is shown together with the other eye from the frame before. The
+// left eye goes to red as gray, the right keeps its green and blue.
+pub struct Anaglyph {
+    composite: Effect,
+    copy: Effect,
+    current: ShaderUniform<i32>,
+    previous: ShaderUniform<i32>,
+    current_is_left: ShaderUniform<i32>,
+    composite_coord: ShaderAttribute<Vector2<f32>>,
+    source: ShaderUniform<i32>,
+    copy_coord: ShaderAttribute<Vector2<f32>>,
+    quad: GPUVec<Vector2<f32>>, // Screen-filling quad
+    framebuffers: FramebufferManager,
+    last: RenderTarget, // The frame before, seen by the other eye
+    size: (f32, f32),   // Size `last` was allocated at
+    pub left: bool,     // Which eye the frame being rendered is for
+}
+
+impl Anaglyph {
+    // Needs the window's GL context, so create it after opening the window
+    pub fn new() -> Anaglyph {
+        let quad = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+        let mut quad = GPUVec::new(quad, BufferType::Array, AllocationType::StaticDraw);
+        quad.load_to_gpu();
+        quad.unload_from_ram();
+
+        let mut composite = Effect::new_from_str(VERTEX_SHADER, COMPOSITE_SHADER);
+        composite.use_program();
+        let current = composite.get_uniform("current").unwrap();
+        let previous = composite.get_uniform("previous").unwrap();
+        let current_is_left = composite.get_uniform("current_is_left").unwrap();
+        let composite_coord = composite.get_attrib("v_coord").unwrap();
+
+        let mut copy = Effect::new_from_str(VERTEX_SHADER, COPY_SHADER);
+        copy.use_program();
+        let source = copy.get_uniform("source").unwrap();
+        let copy_coord = copy.get_attrib("v_coord").unwrap();
+
+        Anaglyph {
+            composite,
+            copy,
+            current,
+            previous,
+            current_is_left,
+            composite_coord,
+            source,
+            copy_coord,
+            quad,
+            framebuffers: FramebufferManager::new(),
+            last: FramebufferManager::new_render_target(800, 600, false),
+            size: (800.0, 600.0),
+            left: true,
+        }
+    }
+}
+
+impl PostProcessingEffect for Anaglyph {
+    fn update(&mut self, _: f32, width: f32, height: f32, _: f32, _: f32) {
+        if self.size != (width, height) {
+            self.last.resize(width, height);
+            self.size = (width, height);
+        }
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+
+        // Both eyes onto the screen
+        self.composite.use_program();
+        self.composite_coord.enable();
+        ctxt.clear_color(0.0, 0.0, 0.0, 1.0);
+        ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT);
+        ctxt.active_texture(Context::TEXTURE0);
+        ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id());
+        ctxt.active_texture(Context::TEXTURE1);
+        ctxt.bind_texture(Context::TEXTURE_2D, self.last.texture_id());
+        self.current.upload(&0);
+        self.previous.upload(&1);
+        self.current_is_left.upload(&i32::from(self.left));
+        self.composite_coord.bind(&mut self.quad);
+        ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4);
+        self.composite_coord.disable();
+
+        // Keep this eye for the next frame, which renders the other one
+        self.framebuffers.select(&self.last);
+        self.copy.use_program();
+        self.copy_coord.enable();
+        ctxt.active_texture(Context::TEXTURE0);
+        ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id());
+        self.source.upload(&0);
+        self.copy_coord.bind(&mut self.quad);
+        ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4);
+        self.copy_coord.disable();
+        self.framebuffers.select(&FramebufferManager::screen());
+
+        self.left = !self.left;
+    }
+}
+
+static VERTEX_SHADER: &str = "#version 100
+    attribute vec2 v_coord;
+    varying vec2   f_texcoord;
+
+    void main(void) {
+      gl_Position = vec4(v_coord, 0.0, 1.0);
+      f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+// Half-color anaglyph: gray for the red (left) eye eases rivalry between
+// the two images, the right eye keeps its color in green and blue
+static COMPOSITE_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D current;
+    uniform sampler2D previous;
+    uniform int       current_is_left;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      vec3 now    = texture2D(current, f_texcoord).rgb;
+      vec3 before = texture2D(previous, f_texcoord).rgb;
+      vec3 left   = current_is_left == 1 ? now : before;
+      vec3 right  = current_is_left == 1 ? before : now;
+      float gray  = dot(left, vec3(0.299, 0.587, 0.114));
+      gl_FragColor = vec4(gray, right.g, right.b, 1.0);
+    }";
+
+static COPY_SHADER: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D source;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      gl_FragColor = texture2D(source, f_texcoord);
+    }";
@@ -0,0 +1,125 @@
+use crate::rgba::Rgb;
+use kiss3d::light::Light;
+use kiss3d::nalgebra::Point3;
+
+const ABOVE_HEIGHT: f32 = 2000.0; // Height of the fixed overhead light
+
+// Where the scene's light comes from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lighting {
+    Camera, // Follows the camera, so whatever faces the viewer is lit
+    Above,  // Fixed overhead, so tops stay lit as the camera moves
+}
+
+impl Lighting {
+    pub fn from_name(name: &str) -> Option<Lighting> {
+        match name {
+            "camera" => Some(Lighting::Camera),
+            "above" => Some(Lighting::Above),
+            _ => None,
+        }
+    }
+
+    pub fn light(self) -> Light {
+        match self {
+            Lighting::Camera => Light::StickToCamera,
+            Lighting::Above => Light::Absolute(Point3::new(0.0, ABOVE_HEIGHT, 0.0)),
+        }
+    }
+}
+
+// Strength of each light, 1 being kiss3d's single light; 0 turns one off
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intensity {
+    pub key: f32,    // The camera or overhead light
+    pub fill: f32,   // Soft light from below, opening up the undersides
+    pub sphere: f32, // Light carried by the sphere in its own color
+}
+
+// Gradient sky colors, straight up, at the horizon and straight down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sky {
+    pub zenith: Rgb,
+    pub horizon: Rgb,
+    pub ground: Rgb,
+}
+
+// Colors and lighting around the trajectory itself
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub background: Rgb,
+    pub grid: Option<Rgb>, // Grid line color, None hides the grid
+    pub text: Rgb,         // HUD and label text
+    pub lighting: Lighting,
+    pub intensity: Intensity,
+    pub sky: Sky, // Shown instead of the flat background with --sky
+}
+
+// Built-in themes selectable with --theme, first is the default
+pub const THEMES: [(&str, Theme); 3] = [
+    (
+        "dark",
+        Theme {
+            background: (0.05, 0.05, 0.1),
+            grid: Some((0.3, 0.3, 0.4)),
+            text: (0.9, 0.9, 0.95),
+            lighting: Lighting::Camera,
+            intensity: Intensity {
+                key: 1.0,
+                fill: 0.25,
+                sphere: 0.8,
+            },
+            sky: Sky {
+                zenith: (0.01, 0.01, 0.05),
+                horizon: (0.14, 0.12, 0.26),
+                ground: (0.03, 0.03, 0.06),
+            },
+        },
+    ),
+    // White page with soft gray grid for printed figures
+    (
+        "light",
+        Theme {
+            background: (1.0, 1.0, 1.0),
+            grid: Some((0.8, 0.8, 0.84)),
+            text: (0.1, 0.1, 0.15),
+            lighting: Lighting::Above,
+            intensity: Intensity {
+                key: 1.0,
+                fill: 0.4,
+                sphere: 0.3,
+            },
+            sky: Sky {
+                zenith: (0.72, 0.82, 0.95),
+                horizon: (1.0, 1.0, 1.0),
+                ground: (0.9, 0.89, 0.86),
+            },
+        },
+    ),
+    (
+        "high-contrast",
+        Theme {
+            background: (0.0, 0.0, 0.0),
+            grid: Some((0.6, 0.6, 0.6)),
+            text: (1.0, 1.0, 1.0),
+            lighting: Lighting::Camera,
+            intensity: Intensity {
+                key: 1.0,
+                fill: 0.0,
+                sphere: 0.6,
+            },
+            sky: Sky {
+                zenith: (0.0, 0.0, 0.0),
+                horizon: (0.2, 0.2, 0.3),
+                ground: (0.0, 0.0, 0.0),
+            },
+        },
+    ),
+];
+
+pub fn theme(name: &str) -> Option<Theme> {
+    THEMES
+        .iter()
+        .find(|(theme, _)| *theme == name)
+        .map(|&(_, theme)| theme)
+}
@@ -0,0 +1,50 @@
+// How much of the path stays drawn behind the sphere
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trail {
+    Last(usize), // The most recent keyframes only
+    All,         // Everything reached so far
+    Decay(f32),  // Reached segments fade out over this many seconds of piece time
+    Piece,       // The whole path from the start, unreached parts dimmed
+}
+
+impl Trail {
+    // Parse "last:<n>", "all", "decay:<seconds>" or "piece"
+    pub fn from_spec(spec: &str) -> Option<Trail> {
+        match spec.split_once(':') {
+            None => match spec {
+                "all" => Some(Trail::All),
+                "piece" => Some(Trail::Piece),
+                _ => None,
+            },
+            Some(("last", value)) => match value.parse() {
+                Ok(n) if n >= 1 => Some(Trail::Last(n)),
+                _ => None,
+            },
+            Some(("decay", value)) => match value.parse() {
+                Ok(seconds) if seconds > 0.0 => Some(Trail::Decay(seconds)),
+                _ => None,
+            },
+            Some(_) => None,
+        }
+    }
+
+    // Inverse of from_spec
+    pub fn spec(self) -> String {
+        match self {
+            Trail::Last(n) => format!("last:{n}"),
+            Trail::All => "all".into(),
+            Trail::Decay(seconds) => format!("decay:{seconds}"),
+            Trail::Piece => "piece".into(),
+        }
+    }
+
+    // First keyframe drawn when the sphere has reached `index`; the trail
+    // starts after the opening keyframe, where the sphere sets off
+    pub fn first_keyframe(self, index: usize) -> usize {
+        match self {
+            Trail::Last(n) => index.saturating_sub(n - 1).max(1),
+            Trail::All | Trail::Decay(_) | Trail::Piece => 1,
+        }
+    }
+}
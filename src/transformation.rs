@@ -1,4 +1,11 @@
-fn matmul4x4(d: [i32; 4], t: [[i32; 4]; 4]) -> [i32; 4] {
+use crate::analysis;
+use kiss3d::nalgebra::Matrix4;
+
+// Products are accumulated in i64: differences of i32 pitches fit in 33
+// bits and matrix entries are capped at MAX_ENTRY (20 bits), so no sum of
+// four products can overflow
+fn matmul4x4(d: [i64; 4], t: [[i32; 4]; 4]) -> [i64; 4] {
+    let t = t.map(|row| row.map(i64::from));
     [
         d[0] * t[0][0] + d[1] * t[0][1] + d[2] * t[0][2] + d[3] * t[0][3],
         d[0] * t[1][0] + d[1] * t[1][1] + d[2] * t[1][2] + d[3] * t[1][3],
@@ -7,27 +14,581 @@ fn matmul4x4(d: [i32; 4], t: [[i32; 4]; 4]) -> [i32; 4] {
     ]
 }
 
-fn matdif4x1(a: [i32; 4], b: [i32; 4]) -> [i32; 4] {
-    [b[0] - a[0], b[1] - a[1], b[2] - a[2], b[3] - a[3]]
+fn matdif4x1<T: Into<i64> + Copy>(a: [T; 4], b: [T; 4]) -> [i64; 4] {
+    [0, 1, 2, 3].map(|i| b[i].into() - a[i].into())
+}
+
+pub type Matrix = [[i32; 4]; 4];
+pub type Chord = [i32; 4]; // Voice pitches
+pub type MotionVec = [f32; 4]; // [total, x, y, z] in semitone units
+pub type Coords = [i64; 4]; // Exact [total, x, y, z] before weighting
+
+// Largest matrix entry magnitude accepted (keeps i64 accumulation exact)
+pub const MAX_ENTRY: i32 = 1 << 20;
+
+// Shared origin for absolute positions: every voice on middle C
+pub const MIDDLE_C: Chord = [60; 4];
+
+// Radius of the circle of fifths in semitone units
+const FIFTHS_RADIUS: f32 = 24.0;
+// Height between neighbouring chord-quality levels
+const QUALITY_STEP: i32 = 6;
+// Torus radii for the DFT phase space: around the ring and around the tube
+const DFT_RING_RADIUS: f32 = 24.0;
+const DFT_TUBE_RADIUS: f32 = 8.0;
+
+pub const DEFAULT_BASIS: Matrix = [
+    [1, 1, 1, 1],   // total motion
+    [1, -1, -1, 1], // x contrary
+    [1, -1, 1, -1], // y contrary
+    [1, 1, -1, -1], // z contrary
+];
+
+// Built-in bases selectable with --basis (voices ordered top to bottom)
+pub const BASES: [(&str, Matrix); 4] = [
+    ("contrary", DEFAULT_BASIS),
+    // Sylvester-ordered Hadamard matrix: the same axes, Walsh sequency order
+    (
+        "hadamard",
+        [[1, 1, 1, 1], [1, -1, 1, -1], [1, 1, -1, -1], [1, -1, -1, 1]],
+    ),
+    // Outer voices against inner voices, then each pair on its own
+    (
+        "satb-pairs",
+        [[1, 1, 1, 1], [1, -1, -1, 1], [1, 0, 0, -1], [0, 1, -1, 0]],
+    ),
+    // Centre of the chord, then its spread, curvature and skew (orthogonal
+    // polynomial contrasts across the voices)
+    (
+        "register-spread",
+        [[1, 1, 1, 1], [3, 1, -1, -3], [1, -1, -1, 1], [1, -3, 3, -1]],
+    ),
+];
+
+pub fn basis(name: &str) -> Option<Matrix> {
+    BASES.iter().find(|(n, _)| *n == name).map(|(_, m)| *m)
 }
 
-fn transform(start: [i32; 4], end: [i32; 4]) -> [i32; 4] {
+// Basis image of the motion from one chord to another
+fn transform(start: Chord, end: Chord, basis: Matrix) -> Coords {
     let d = matdif4x1(start, end);
-    let t: [[i32; 4]; 4] = [
-        [1, 1, 1, 1],   // total motion
-        [1, -1, -1, 1], // x contrary
-        [1, -1, 1, -1], // y contrary
-        [1, 1, -1, -1], // z contrary
-    ];
-    return matmul4x4(d, t);
-}
-
-pub fn convert(voice_leadings: Vec<[i32; 4]>) -> Vec<[i32; 4]> {
-    let mut out: Vec<[i32; 4]> = Vec::<[i32; 4]>::new();
-    for i in 0..(voice_leadings.len() - 1) {
-        let cur = voice_leadings[i];
-        let next = voice_leadings[i + 1];
-        out.push(transform(cur, next));
-    }
-    return out;
+    matmul4x4(d, basis)
+}
+
+// Parse a 4x4 integer matrix: rows separated by ';' or newlines, entries by
+// ',' or whitespace, '#' starts a comment
+pub fn parse_matrix(text: &str) -> Result<Matrix, String> {
+    let rows: Vec<&str> = text
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or(""))
+        .flat_map(|line| line.split(';'))
+        .map(str::trim)
+        .filter(|row| !row.is_empty())
+        .collect();
+    if rows.len() != 4 {
+        return Err(format!("matrix needs 4 rows, found {}", rows.len()));
+    }
+
+    let mut matrix = [[0; 4]; 4];
+    for (i, row) in rows.iter().enumerate() {
+        let entries: Vec<&str> = row
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|e| !e.is_empty())
+            .collect();
+        if entries.len() != 4 {
+            return Err(format!(
+                "matrix row {} needs 4 entries, found {}",
+                i + 1,
+                entries.len()
+            ));
+        }
+        for (j, entry) in entries.iter().enumerate() {
+            matrix[i][j] = entry
+                .parse()
+                .map_err(|_| format!("matrix entry '{entry}' is not an integer"))?;
+        }
+        if matrix[i]
+            .iter()
+            .any(|e: &i32| e.unsigned_abs() > MAX_ENTRY as u32)
+        {
+            return Err(format!(
+                "matrix row {} has an entry larger than {MAX_ENTRY} in magnitude",
+                i + 1
+            ));
+        }
+        if matrix[i] == [0; 4] {
+            return Err(format!("matrix row {} is all zeros", i + 1));
+        }
+    }
+
+    Ok(matrix)
+}
+
+// Condition number above which a basis is reported as ill-conditioned
+const MAX_CONDITION: f32 = 10.0;
+
+// Properties of a basis matrix reported to the user
+pub struct Diagnostics {
+    pub determinant: i128,
+    pub orthogonal: bool, // Rows pairwise orthogonal
+    pub condition: f32,   // Ratio of largest to smallest singular value
+}
+
+fn minor(m: &[[i128; 4]; 4], rows: &[usize], cols: &[usize]) -> i128 {
+    if rows.len() == 1 {
+        return m[rows[0]][cols[0]];
+    }
+    // Laplace expansion along the first remaining row
+    let mut det = 0;
+    for (k, &col) in cols.iter().enumerate() {
+        let rest: Vec<usize> = cols.iter().copied().filter(|&c| c != col).collect();
+        let sign = if k % 2 == 0 { 1 } else { -1 };
+        det += sign * m[rows[0]][col] * minor(m, &rows[1..], &rest);
+    }
+    det
+}
+
+// Exact determinant of an integer matrix
+pub fn determinant(matrix: Matrix) -> i128 {
+    let wide = matrix.map(|row| row.map(i128::from));
+    minor(&wide, &[0, 1, 2, 3], &[0, 1, 2, 3])
+}
+
+// Check a basis, refusing rank-deficient matrices
+pub fn diagnose(matrix: Matrix) -> Result<Diagnostics, String> {
+    let determinant = determinant(matrix);
+    if determinant == 0 {
+        return Err(
+            "matrix is singular (determinant 0): some voice motions would be \
+             indistinguishable, so rows must be linearly independent"
+                .into(),
+        );
+    }
+
+    let dot = |a: [i32; 4], b: [i32; 4]| (0..4).map(|k| a[k] as i64 * b[k] as i64).sum::<i64>();
+    let orthogonal = (0..4).all(|i| (i + 1..4).all(|j| dot(matrix[i], matrix[j]) == 0));
+
+    let singular = Matrix4::from_fn(|i, j| matrix[i][j] as f32).singular_values();
+    let condition = singular.max() / singular.min();
+
+    Ok(Diagnostics {
+        determinant,
+        orthogonal,
+        condition,
+    })
+}
+
+// What an output axis measures, in terms of voice motion
+pub fn describe_axis(row: [i32; 4]) -> String {
+    let term = |(i, &c): (usize, &i32)| match c {
+        0 => None,
+        1 => Some(format!("+v{}", i + 1)),
+        -1 => Some(format!("-v{}", i + 1)),
+        c => Some(format!("{c:+}·v{}", i + 1)),
+    };
+    let formula: Vec<String> = row.iter().enumerate().filter_map(term).collect();
+    let with: Vec<String> = (0..4)
+        .filter(|&i| row[i] > 0)
+        .map(|i| format!("v{}", i + 1))
+        .collect();
+    let against: Vec<String> = (0..4)
+        .filter(|&i| row[i] < 0)
+        .map(|i| format!("v{}", i + 1))
+        .collect();
+
+    let meaning = match (with.len(), against.len()) {
+        (_, 0) | (0, _) if with.len() + against.len() == 4 => "all voices together".to_string(),
+        (_, 0) | (0, _) => format!("{} together", [with, against].concat().join(", ")),
+        _ => format!("{} against {}", with.join(", "), against.join(", ")),
+    };
+    format!("{} ({meaning})", formula.join(" "))
+}
+
+// Short on-screen name of an axis: the familiar contrary patterns by name,
+// anything else by its formula
+pub fn axis_label(row: [i32; 4]) -> String {
+    let pattern = if row[0] < 0 { row.map(|c| -c) } else { row };
+    match pattern {
+        [1, 1, 1, 1] => "total motion".into(),
+        [1, -1, -1, 1] => "outer-voice contrary".into(),
+        [1, -1, 1, -1] => "alternating contrary".into(),
+        [1, 1, -1, -1] => "upper-pair contrary".into(),
+        _ => describe_axis(row),
+    }
+}
+
+// Readable report of a basis: each axis, then its numerical health
+pub fn basis_report(matrix: Matrix, diagnostics: &Diagnostics) -> Vec<String> {
+    let names = ["total", "x", "y", "z"];
+    let mut lines: Vec<String> = (0..4)
+        .map(|i| format!("{:>5} = {}", names[i], describe_axis(matrix[i])))
+        .collect();
+    lines.push(format!("determinant {}", diagnostics.determinant));
+    if !diagnostics.orthogonal {
+        lines.push("rows are not orthogonal: axes mix overlapping voice motions".into());
+    }
+    if diagnostics.condition > MAX_CONDITION {
+        lines.push(format!(
+            "ill-conditioned (condition number {:.1}): small motions may be exaggerated along some axes",
+            diagnostics.condition
+        ));
+    } else {
+        lines.push(format!("condition number {:.2}", diagnostics.condition));
+    }
+    lines
+}
+
+// Voice displacements of every voice leading whose total work (sum of
+// semitones moved by all voices) is exactly `distance`
+pub fn voice_steps(distance: i32) -> Vec<Chord> {
+    let mut out = Vec::new();
+    let range = -distance..=distance;
+    for a in range.clone() {
+        for b in range.clone() {
+            for c in range.clone() {
+                let rest = distance - a.abs() - b.abs() - c.abs();
+                if rest < 0 {
+                    continue;
+                }
+                // The last voice takes up whatever work is left, either way
+                for d in if rest == 0 {
+                    vec![0]
+                } else {
+                    vec![rest, -rest]
+                } {
+                    out.push([a, b, c, d]);
+                }
+            }
+        }
+    }
+    out
+}
+
+// Reduce every voice to its pitch class (0-11)
+pub fn fold_pitch_classes(voice_leadings: &[[i32; 4]]) -> Vec<[i32; 4]> {
+    voice_leadings
+        .iter()
+        .map(|chord| chord.map(|note| note.rem_euclid(12)))
+        .collect()
+}
+
+// Canonical representative of each chord in the four-note orbifold T⁴/S₄
+// (Tymoczko, "A Geometry of Music"): voices become sorted pitch classes
+// spanning less than an octave, rotated (lowest note up an octave and back
+// down) until the sum lies in 0..12, so every chord has a single location
+pub fn fold_orbifold(voice_leadings: &[[i32; 4]]) -> Vec<[i32; 4]> {
+    voice_leadings
+        .iter()
+        .map(|chord| {
+            let mut notes = chord.map(|note| note.rem_euclid(12));
+            notes.sort_unstable();
+            let sum: i32 = notes.iter().sum();
+            for _ in 0..sum / 12 {
+                notes = [notes[3] - 12, notes[0], notes[1], notes[2]];
+            }
+            notes
+        })
+        .collect()
+}
+
+// Absolute location of a chord under the basis
+pub fn position(chord: Chord, basis: Matrix) -> Coords {
+    matmul4x4(chord.map(i64::from), basis)
+}
+
+// Voice-leading size metrics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    Taxicab,   // L1: total semitones moved by all voices
+    Euclidean, // L2
+    Chebyshev, // L∞: the largest single-voice move
+}
+
+impl Metric {
+    pub const ALL: [Metric; 3] = [Metric::Taxicab, Metric::Euclidean, Metric::Chebyshev];
+
+    pub fn from_name(name: &str) -> Option<Metric> {
+        match name {
+            "l1" => Some(Metric::Taxicab),
+            "l2" => Some(Metric::Euclidean),
+            "linf" => Some(Metric::Chebyshev),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Taxicab => "L1",
+            Metric::Euclidean => "L2",
+            Metric::Chebyshev => "L∞",
+        }
+    }
+}
+
+// Size of every voice-leading step under a metric
+pub fn voice_leading_sizes(voice_leadings: &[[i32; 4]], metric: Metric) -> Vec<f32> {
+    voice_leadings
+        .windows(2)
+        .map(|w| {
+            let d = matdif4x1(w[0], w[1]).map(|v| v.abs() as f32);
+            match metric {
+                Metric::Taxicab => d.iter().sum(),
+                Metric::Euclidean => d.iter().map(|v| v * v).sum::<f32>().sqrt(),
+                Metric::Chebyshev => d.iter().copied().fold(0.0, f32::max),
+            }
+        })
+        .collect()
+}
+
+// Relative motion of two voices over one step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotionType {
+    Static,   // Neither voice moves
+    Parallel, // Same direction, same interval
+    Similar,  // Same direction, different intervals
+    Oblique,  // One voice holds while the other moves
+    Contrary, // Opposite directions
+}
+
+impl MotionType {
+    pub fn letter(self) -> char {
+        match self {
+            MotionType::Static => '-',
+            MotionType::Parallel => 'P',
+            MotionType::Similar => 'S',
+            MotionType::Oblique => 'O',
+            MotionType::Contrary => 'C',
+        }
+    }
+}
+
+// Voice pairs in the order classify() reports them
+pub const VOICE_PAIRS: [(usize, usize); 6] = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)];
+
+fn motion_type(a: i64, b: i64) -> MotionType {
+    match (a, b) {
+        (0, 0) => MotionType::Static,
+        (0, _) | (_, 0) => MotionType::Oblique,
+        _ if a.signum() != b.signum() => MotionType::Contrary,
+        _ if a == b => MotionType::Parallel,
+        _ => MotionType::Similar,
+    }
+}
+
+// Motion type of every voice pair for each step, aligned with convert()
+pub fn classify(voice_leadings: &[[i32; 4]]) -> Vec<[MotionType; 6]> {
+    voice_leadings
+        .windows(2)
+        .map(|w| {
+            let d = matdif4x1(w[0], w[1]);
+            VOICE_PAIRS.map(|(i, j)| motion_type(d[i], d[j]))
+        })
+        .collect()
+}
+
+// Compact per-step label such as "PS-OCC", one letter per voice pair
+pub fn motion_label(types: &[MotionType; 6]) -> String {
+    types.iter().map(|t| t.letter()).collect()
+}
+
+// Circle-of-fifths location of each chord: the root (or the bass when the
+// chord has no name) sets the angle around y, quality brightness the height
+// and the summed pitches the total component, so functional progressions
+// turn around the axis
+pub fn fifths_points(voice_leadings: &[[i32; 4]]) -> Vec<Coords> {
+    voice_leadings
+        .iter()
+        .map(|chord| {
+            let (root, level) = match analysis::detect_chord(*chord) {
+                Some(name) => (name.root, name.quality.brightness()),
+                None => (chord.iter().min().unwrap().rem_euclid(12), 0),
+            };
+            let angle = ((root * 7) % 12) as f32 * std::f32::consts::TAU / 12.0;
+            [
+                chord.iter().map(|&p| i64::from(p)).sum(),
+                (FIFTHS_RADIUS * angle.sin()).round() as i64,
+                (level * QUALITY_STEP) as i64,
+                (FIFTHS_RADIUS * angle.cos()).round() as i64,
+            ]
+        })
+        .collect()
+}
+
+// Coefficient k of the discrete Fourier transform of a chord's pitch
+// classes (voices counted with multiplicity), as (magnitude, phase)
+pub fn dft_coefficient(chord: Chord, k: i32) -> (f32, f32) {
+    let (mut re, mut im) = (0.0f32, 0.0f32);
+    for &pitch in &chord {
+        let angle = -std::f32::consts::TAU * (k * pitch.rem_euclid(12)) as f32 / 12.0;
+        re += angle.cos();
+        im += angle.sin();
+    }
+    (re.hypot(im), im.atan2(re))
+}
+
+// DFT phase space: the phase of f5 (fifth-ness) runs around a torus ring
+// and the phase of f3 (triadic-ness) around its tube, so chords of one key
+// area cluster and the circular phases never jump. Where a coefficient
+// vanishes its phase is undefined and the previous chord's is kept.
+pub fn dft_points(voice_leadings: &[[i32; 4]]) -> Vec<MotionVec> {
+    let (mut phase5, mut phase3) = (0.0, 0.0);
+    voice_leadings
+        .iter()
+        .map(|&chord| {
+            let (magnitude5, p5) = dft_coefficient(chord, 5);
+            let (magnitude3, p3) = dft_coefficient(chord, 3);
+            if magnitude5 > 1e-3 {
+                phase5 = p5;
+            }
+            if magnitude3 > 1e-3 {
+                phase3 = p3;
+            }
+            let ring = DFT_RING_RADIUS + DFT_TUBE_RADIUS * phase3.cos();
+            [
+                chord.iter().sum::<i32>() as f32,
+                ring * phase5.cos(),
+                DFT_TUBE_RADIUS * phase3.sin(),
+                ring * phase5.sin(),
+            ]
+        })
+        .collect()
+}
+
+// Scale each output axis of integer motion vectors, e.g. to de-emphasize
+// total motion or exaggerate one contrary axis
+pub fn weigh(motions: &[Coords], weights: [f32; 4]) -> Vec<MotionVec> {
+    motions
+        .iter()
+        .map(|m| [0, 1, 2, 3].map(|j| m[j] as f32 * weights[j]))
+        .collect()
+}
+
+// Express each motion per beat of the time it takes (durations in 16th
+// notes), so dense figuration and slow chorales become comparable
+pub fn per_beat(motions: &[MotionVec], durations: &[f32]) -> Vec<MotionVec> {
+    motions
+        .iter()
+        .zip(durations)
+        .map(|(m, &sixteenths)| {
+            let beats = (sixteenths / 4.0).max(1e-3);
+            m.map(|v| v / beats)
+        })
+        .collect()
+}
+
+// Motion between consecutive absolute points
+pub fn deltas(points: &[Coords]) -> Vec<Coords> {
+    points.windows(2).map(|w| matdif4x1(w[0], w[1])).collect()
+}
+
+// Like convert(), with the mean voice motion (the transposition part of
+// each step) removed first, so a passage repeated in another key traces
+// the same shape. Results are scaled by TRANSPOSITION_SCALE to stay exact.
+pub const TRANSPOSITION_SCALE: i64 = 4;
+
+pub fn convert_without_transposition(voice_leadings: &[[i32; 4]], basis: Matrix) -> Vec<Coords> {
+    voice_leadings
+        .windows(2)
+        .map(|pair| {
+            let d = matdif4x1(pair[0], pair[1]);
+            let sum: i64 = d.iter().sum();
+            matmul4x4(d.map(|v| TRANSPOSITION_SCALE * v - sum), basis)
+        })
+        .collect()
+}
+
+pub fn convert(voice_leadings: Vec<[i32; 4]>, basis: Matrix) -> Vec<Coords> {
+    let mut out: Vec<Coords> = Vec::<Coords>::new();
+    for pair in voice_leadings.windows(2) {
+        out.push(transform(pair[0], pair[1], basis));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_midi_range_leap_is_exact() {
+        let motions = convert(vec![[0; 4], [127; 4]], DEFAULT_BASIS);
+        assert_eq!(motions, vec![[508, 0, 0, 0]]);
+
+        let motions = convert(vec![[127, 0, 127, 0], [0, 127, 0, 127]], DEFAULT_BASIS);
+        assert_eq!(motions, vec![[0, 0, -508, 0]]);
+    }
+
+    #[test]
+    fn extreme_pitches_do_not_overflow() {
+        let low = [i32::MIN; 4];
+        let high = [i32::MAX; 4];
+        let span = i64::from(i32::MAX) - i64::from(i32::MIN);
+        assert_eq!(
+            convert(vec![low, high], DEFAULT_BASIS),
+            vec![[4 * span, 0, 0, 0]]
+        );
+        assert_eq!(position(high, DEFAULT_BASIS)[0], 4 * i64::from(i32::MAX));
+        assert_eq!(classify(&[low, high])[0], [MotionType::Parallel; 6]);
+        assert_eq!(
+            voice_leading_sizes(&[low, high], Metric::Chebyshev)[0],
+            span as f32
+        );
+    }
+
+    #[test]
+    fn largest_entries_with_extreme_pitches_do_not_overflow() {
+        let basis = [
+            [MAX_ENTRY; 4],
+            [-MAX_ENTRY; 4],
+            [MAX_ENTRY, 0, 0, 0],
+            [0, 0, 0, 1],
+        ];
+        let motions = convert(vec![[i32::MIN; 4], [i32::MAX; 4]], basis);
+        let span = i64::from(i32::MAX) - i64::from(i32::MIN);
+        assert_eq!(motions[0][0], 4 * span * i64::from(MAX_ENTRY));
+        assert_eq!(motions[0][1], -4 * span * i64::from(MAX_ENTRY));
+    }
+
+    #[test]
+    fn oversized_entries_are_rejected() {
+        let text = format!("{},0,0,0; 0,1,0,0; 0,0,1,0; 0,0,0,1", MAX_ENTRY + 1);
+        assert!(parse_matrix(&text).is_err());
+        let text = format!("{},0,0,0; 0,1,0,0; 0,0,1,0; 0,0,0,1", i32::MIN);
+        assert!(parse_matrix(&text).is_err());
+        assert!(parse_matrix("99999999999,0,0,0; 0,1,0,0; 0,0,1,0; 0,0,0,1").is_err());
+    }
+
+    #[test]
+    fn determinant_of_large_entries_is_exact() {
+        assert_eq!(determinant(DEFAULT_BASIS).abs(), 16);
+        let m = MAX_ENTRY;
+        let diagonal = [[m, 0, 0, 0], [0, m, 0, 0], [0, 0, m, 0], [0, 0, 0, m]];
+        assert_eq!(determinant(diagonal), i128::from(m).pow(4));
+        assert!(diagnose([[1, 1, 1, 1], [2, 2, 2, 2], [0, 1, 0, 0], [0, 0, 1, 0]]).is_err());
+    }
+
+    #[test]
+    fn pitch_class_folds_handle_negative_and_high_pitches() {
+        assert_eq!(fold_pitch_classes(&[[-1, 127, 0, 12]]), vec![[11, 7, 0, 0]]);
+        let folded = fold_orbifold(&[[i32::MAX, i32::MIN, 0, 127]]);
+        let sum: i32 = folded[0].iter().sum();
+        assert!((0..12).contains(&sum));
+    }
+
+    #[test]
+    fn voice_steps_are_every_leading_of_that_size() {
+        for (distance, count) in [(1, 8), (2, 32), (3, 88)] {
+            let steps = voice_steps(distance);
+            assert_eq!(steps.len(), count);
+            assert!(
+                steps
+                    .iter()
+                    .all(|step| step.iter().map(|v| v.abs()).sum::<i32>() == distance)
+            );
+            let mut distinct = steps.clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+            assert_eq!(distinct.len(), count);
+        }
+    }
 }
@@ -0,0 +1,214 @@
+use crate::rgba::Palette;
+use image::{DynamicImage, ImageBuffer, Rgb};
+use kiss3d::nalgebra::{Point2, Point3, Vector3};
+use kiss3d::resource::{Mesh, Texture, TextureManager};
+use kiss3d::scene::SceneNode;
+use kiss3d::window::Window;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const TUBE_SIDES: usize = 8; // Vertices around each ring
+const CHUNK_RINGS: usize = 4096; // Rings per mesh, keeping vertex indices within u16
+const RADIUS_RANGE: (f32, f32) = (1.5, 10.0); // Radius at the smallest and at the largest step
+const TUBE_SATURATION: f32 = 0.6; // The same softened hues as the line trail
+const HUE_STEPS: u32 = 256; // Width of the hue texture
+
+// One ring of vertices around the path, with the hue it is colored by
+#[derive(Clone, Copy)]
+struct Ring {
+    vertices: [Point3<f32>; TUBE_SIDES],
+    hue: f32,
+}
+
+// The trail as a tube whose thickness follows the size of each voice
+// leading, extended ring by ring as keyframes are reached. Colors come
+// from a hue strip texture, so one mesh can carry every hue; meshes hold
+// CHUNK_RINGS rings each, consecutive ones sharing their boundary ring.
+pub struct Tube {
+    radii: Vec<f32>,               // Radius of the ring at every keyframe
+    texture: Rc<Texture>,          // Hue strip, sampled at each ring's hue
+    chunks: Vec<SceneNode>,        // Meshes, oldest first
+    chunk_rings: usize,            // Rings in the newest mesh
+    built: Option<(usize, usize)>, // First and last keyframe in the meshes
+    last: Option<Ring>,            // Newest ring, where the next segment starts
+    tangent: Vector3<f32>,         // Path direction at the newest ring
+    normal: Vector3<f32>,          // Ring orientation carried along the path
+    visible: bool,
+}
+
+impl Tube {
+    // `sizes` holds the voice-leading size of every step and `palette`
+    // colors the hue texture; needs the window's GL context for the
+    // texture, so create it after opening one
+    pub fn new(sizes: &[f32], palette: Palette) -> Tube {
+        let largest = sizes.iter().copied().fold(0.0, f32::max);
+        let (low, high) = RADIUS_RANGE;
+        let radii = (0..=sizes.len())
+            .map(|k| match sizes.get(k.saturating_sub(1)) {
+                Some(size) if largest > 0.0 => low + (high - low) * size / largest,
+                _ => low,
+            })
+            .collect();
+
+        let strip = ImageBuffer::from_fn(HUE_STEPS, 1, |x, _| {
+            let (r, g, b) = palette.soft(x as f32 / HUE_STEPS as f32, TUBE_SATURATION);
+            Rgb([r, g, b].map(|c| (c * 255.0).round() as u8))
+        });
+        let texture = TextureManager::get_global_manager(|manager| {
+            manager.add_image(DynamicImage::ImageRgb8(strip.clone()), "tube-hues")
+        });
+
+        Tube {
+            radii,
+            texture,
+            chunks: Vec::new(),
+            chunk_rings: 0,
+            built: None,
+            last: None,
+            tangent: Vector3::x(),
+            normal: Vector3::y(),
+            visible: true,
+        }
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+        for chunk in &mut self.chunks {
+            chunk.set_visible(visible);
+        }
+    }
+
+    // Vertices in the meshes, counting the rings shared between meshes twice
+    pub fn vertex_count(&self) -> usize {
+        match self.built {
+            Some((first, last)) if !self.chunks.is_empty() => {
+                (last - first + self.chunks.len()) * TUBE_SIDES
+            }
+            _ => 0,
+        }
+    }
+
+    // Drop every ring, e.g. when the path has been reprojected
+    pub fn clear(&mut self, window: &mut Window) {
+        for mut chunk in self.chunks.drain(..) {
+            window.remove_node(&mut chunk);
+        }
+        self.chunk_rings = 0;
+        self.built = None;
+        self.last = None;
+        self.tangent = Vector3::x();
+        self.normal = Vector3::y();
+    }
+
+    // Cover keyframes first..=last of the path, only adding rings when the
+    // trail has grown and rebuilding when it has moved on or gone back
+    pub fn update(
+        &mut self,
+        window: &mut Window,
+        path: &[Point3<f32>],
+        first: usize,
+        last: usize,
+        hue: impl Fn(usize) -> f32,
+    ) {
+        let from = match self.built {
+            Some((built_first, built_last)) if built_first == first && built_last <= last => {
+                built_last + 1
+            }
+            _ => {
+                self.clear(window);
+                first
+            }
+        };
+        if from > last {
+            return;
+        }
+
+        let touched = self.chunks.len().saturating_sub(1);
+        for k in from..=last {
+            let ring = self.ring(path, k, hue(k));
+            if let Some(previous) = self.last {
+                self.join(window, previous, ring);
+            }
+            self.last = Some(ring);
+        }
+        for chunk in &mut self.chunks[touched..] {
+            chunk.recompute_normals();
+        }
+        self.built = Some((first, last));
+    }
+
+    // Ring around keyframe k, turned as little as possible from the
+    // previous ring so the tube does not twist
+    fn ring(&mut self, path: &[Point3<f32>], k: usize, hue: f32) -> Ring {
+        // Held chords repeat a point; keep the previous direction there
+        let ahead = path[(k + 1).min(path.len() - 1)] - path[k.saturating_sub(1)];
+        if let Some(direction) = ahead.try_normalize(1e-6) {
+            self.tangent = direction;
+        }
+        let tangent = self.tangent;
+        let projected = self.normal - tangent * self.normal.dot(&tangent);
+        self.normal = projected.try_normalize(1e-6).unwrap_or_else(|| {
+            let side = if tangent.x.abs() < 0.9 {
+                Vector3::x()
+            } else {
+                Vector3::y()
+            };
+            tangent.cross(&side).normalize()
+        });
+        let binormal = tangent.cross(&self.normal);
+
+        let radius = self.radii.get(k).copied().unwrap_or(RADIUS_RANGE.0);
+        let vertices = std::array::from_fn(|side| {
+            let angle = side as f32 / TUBE_SIDES as f32 * std::f32::consts::TAU;
+            path[k] + (self.normal * angle.cos() + binormal * angle.sin()) * radius
+        });
+        Ring { vertices, hue }
+    }
+
+    // Add the segment between two rings, to the newest mesh while it has room
+    fn join(&mut self, window: &mut Window, from: Ring, to: Ring) {
+        let uvs = |ring: Ring| [Point2::new(ring.hue, 0.5); TUBE_SIDES];
+        if self.chunks.is_empty() || self.chunk_rings == CHUNK_RINGS {
+            let coords = [from.vertices, to.vertices].concat();
+            let mesh = Mesh::new(
+                coords,
+                faces(0),
+                None,
+                Some([uvs(from), uvs(to)].concat()),
+                true,
+            );
+            let mut node =
+                window.add_mesh(Rc::new(RefCell::new(mesh)), Vector3::new(1.0, 1.0, 1.0));
+            node.set_texture(self.texture.clone());
+            node.enable_backface_culling(false);
+            node.set_visible(self.visible);
+            self.chunks.push(node);
+            self.chunk_rings = 2;
+        } else {
+            let base = (self.chunk_rings - 1) * TUBE_SIDES;
+            let chunk = self.chunks.last_mut().expect("a mesh was just checked for");
+            chunk.modify_vertices(&mut |coords| coords.extend_from_slice(&to.vertices));
+            chunk.modify_uvs(&mut |coords| coords.extend_from_slice(&uvs(to)));
+            chunk.modify_faces(&mut |list| list.extend(faces(base)));
+            self.chunk_rings += 1;
+        }
+    }
+}
+
+// Two triangles per side between the ring starting at vertex `base` and the next
+fn faces(base: usize) -> Vec<Point3<u16>> {
+    let mut faces = Vec::with_capacity(2 * TUBE_SIDES);
+    for side in 0..TUBE_SIDES {
+        let next = (side + 1) % TUBE_SIDES;
+        let [a, b, c, d] = [
+            base + side,
+            base + next,
+            base + TUBE_SIDES + next,
+            base + TUBE_SIDES + side,
+        ]
+        .map(|i| i as u16);
+        faces.push(Point3::new(a, b, c));
+        faces.push(Point3::new(a, c, d));
+    }
+    faces
+}
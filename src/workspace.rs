@@ -0,0 +1,105 @@
+use crate::analysis::Span;
+use crate::midi::{self, Fill, KeyframeMode, Keyframes};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// One movement of a multi-movement work
+pub struct Movement {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+fn is_midi(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("mid") || e.eq_ignore_ascii_case("midi"))
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+// List the movements behind an input path: a single MIDI file, a folder of
+// MIDI files (in file name order), or a playlist manifest with one
+// "<file> [| <movement name>]" per line, relative to the manifest
+pub fn movements(path: &Path) -> Result<Vec<Movement>, Box<dyn Error>> {
+    if path.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| is_midi(p))
+            .collect();
+        files.sort();
+        if files.is_empty() {
+            return Err("folder contains no .mid files".into());
+        }
+        return Ok(files
+            .into_iter()
+            .map(|path| Movement {
+                name: stem(&path),
+                path,
+            })
+            .collect());
+    }
+
+    if is_midi(path) {
+        return Ok(vec![Movement {
+            name: stem(path),
+            path: path.to_path_buf(),
+        }]);
+    }
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut out = Vec::new();
+    for (n, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (file, name) = match line.split_once('|') {
+            Some((file, name)) => (file.trim(), name.trim().to_string()),
+            None => (line, stem(Path::new(line))),
+        };
+        let file = dir.join(file);
+        if !file.is_file() {
+            return Err(format!("playlist line {}: {:?} does not exist", n + 1, file).into());
+        }
+        out.push(Movement { name, path: file });
+    }
+    if out.is_empty() {
+        return Err("playlist lists no movements".into());
+    }
+    Ok(out)
+}
+
+// Parse every movement into one continuous piece, each starting on a new
+// bar, together with the keyframe range each movement covers
+pub fn open(
+    movements: &[Movement],
+    sequence: Option<usize>,
+    fill: Fill,
+    mode: KeyframeMode,
+) -> Result<(Keyframes, Vec<Span>), Box<dyn Error>> {
+    let mut keyframes: Option<Keyframes> = None;
+    let mut spans = Vec::new();
+
+    for movement in movements {
+        let part = midi::parse(&movement.path, sequence, fill, mode)
+            .map_err(|e| format!("{}: {e}", movement.name))?;
+        let first = keyframes.as_ref().map_or(0, |k| k.chords.len());
+        let last = first + part.chords.len() - 1;
+        match &mut keyframes {
+            Some(keyframes) => keyframes.append(part),
+            None => keyframes = Some(part),
+        }
+        spans.push(Span {
+            first,
+            last,
+            label: movement.name.clone(),
+        });
+    }
+
+    Ok((keyframes.ok_or("no movements")?, spans))
+}